@@ -0,0 +1,297 @@
+use crate::{
+    board::Board,
+    constraint::Constraint,
+    error::SudokuError,
+    location::{cell_name, parse_cell_names, Location},
+    render::{direction_mark, CIRCLE}
+};
+
+/// A circle of one or more cells, and a path of cells leading away from it whose digits add up to the circle's number.
+/// A circle with several cells reads as a number with their digits written side by side in order, e.g. 1 and 2 make 12,
+/// and on boards with more than 9 digits 1 and 16 make 116.
+/// Digits count as 1 up to the number of digits, whatever symbols draw them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Arrow {
+    circle: Vec<Location>,
+    cells: Vec<Location>,
+}
+
+impl Arrow {
+    /// Panics if the arrow is empty or broken; see `try_new`.
+    pub fn new(circle: Vec<Location>, cells: Vec<Location>) -> Self {
+        match Self::try_new(circle, cells) {
+            Ok(arrow) => arrow,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Takes the circle's cells in reading order and the arrow's cells from the circle to the tip.
+    /// Returns an error if either is empty, or if the arrow's first cell doesn't touch the circle
+    /// or another of its cells doesn't touch the one before it (diagonally counts).
+    /// Digits can repeat along an arrow, except where its cells share a house.
+    /// `Board::try_add_constraint` checks the cells are on the board.
+    pub fn try_new(circle: Vec<Location>, cells: Vec<Location>) -> Result<Self, SudokuError> {
+        if circle.is_empty() || cells.is_empty() {
+            return Err(SudokuError::EmptyArrow);
+        }
+        let touches = |a: Location, b: Location| match (a, b) {
+            (Location::Valid(r1, c1), Location::Valid(r2, c2)) => r1.abs_diff(r2).max(c1.abs_diff(c2)) == 1,
+            _ => false,
+        };
+        if !circle.iter().any(|&location| touches(location, cells[0])) {
+            return Err(SudokuError::BrokenArrow(cells[0]));
+        }
+        if let Some(pair) = cells.windows(2).find(|pair| !touches(pair[0], pair[1])) {
+            return Err(SudokuError::BrokenArrow(pair[1]));
+        }
+        Ok(Self { circle, cells })
+    }
+
+    /// The circle's cells, whose digits make its number.
+    pub fn circle(&self) -> &[Location] {
+        &self.circle
+    }
+
+    /// The cells along the arrow, from the circle to the tip.
+    pub fn cells(&self) -> &[Location] {
+        &self.cells
+    }
+
+    /// Removes candidates that can't make the circle's number equal the arrow's sum, given the smallest and largest
+    /// candidates of every cell. Returns whether it removed any, or `None` if there's no way to make them equal.
+    fn propagate(&self, board: &mut Board) -> Option<bool> {
+        let range = |board: &Board, location: Location| -> Option<(u64, u64)> {
+            let possibilities = &board.get(location).possibilities;
+            Some((possibilities.iter().position(|&possible| possible)? as u64 + 1, possibilities.iter().rposition(|&possible| possible)? as u64 + 1))
+        };
+        let circle: Vec<(u64, u64)> = self.circle.iter().map(|&location| range(board, location)).collect::<Option<_>>()?;
+        let arrow: Vec<(u64, u64)> = self.cells.iter().map(|&location| range(board, location)).collect::<Option<_>>()?;
+        let (smallest_sum, largest_sum) = arrow.iter().fold((0, 0), |(low, high), &(min, max)| (low + min, high + max));
+        let smallest_number = read_number(circle.iter().map(|&(min, _)| min));
+        let largest_number = read_number(circle.iter().map(|&(_, max)| max));
+        let (low, high) = (smallest_sum.max(smallest_number), largest_sum.min(largest_number));
+        if low > high {
+            return None;
+        }
+
+        let mut found_something = false;
+        //Each cell along the arrow makes up what the others can't reach, and can't go past what the others leave.
+        for (&location, &(min, max)) in self.cells.iter().zip(arrow.iter()) {
+            let (others_low, others_high) = (smallest_sum - min, largest_sum - max);
+            let cell = board.get_mut(location);
+            for (digit, possible) in cell.possibilities.iter_mut().enumerate().filter(|(_, possible)| **possible) {
+                let value = digit as u64 + 1;
+                if value + others_high < low || value + others_low > high {
+                    *possible = false;
+                    found_something = true;
+                }
+            }
+        }
+        //Each cell of the circle has to leave the number somewhere in the range, with the others as small or as large as they can be.
+        for (index, &location) in self.circle.iter().enumerate() {
+            let cell = board.get_mut(location);
+            for (digit, possible) in cell.possibilities.iter_mut().enumerate().filter(|(_, possible)| **possible) {
+                let value = digit as u64 + 1;
+                let with = |pick: fn(&(u64, u64)) -> u64| read_number(circle.iter().enumerate().map(|(other, bounds)| if other == index { value } else { pick(bounds) }));
+                if with(|&(_, max)| max) < low || with(|&(min, _)| min) > high {
+                    *possible = false;
+                    found_something = true;
+                }
+            }
+        }
+        Some(found_something)
+    }
+
+    /// Returns true if the solved cells already make the sum too large for the circle, or the two differ once all are solved.
+    fn is_broken(&self, board: &Board) -> bool {
+        let values = |cells: &[Location]| -> Vec<Option<u64>> { cells.iter().map(|&location| board.get(location).get_single_index().map(|digit| digit as u64 + 1)).collect() };
+        let (circle, arrow) = (values(&self.circle), values(&self.cells));
+        //Unsolved cells along the arrow add at least 1 each.
+        let smallest_sum: u64 = arrow.iter().map(|value| value.unwrap_or(1)).sum();
+        match circle.into_iter().collect::<Option<Vec<u64>>>() {
+            Some(circle) => {
+                let number = read_number(circle.into_iter());
+                smallest_sum > number || (arrow.iter().all(Option::is_some) && smallest_sum != number)
+            }
+            None => false,
+        }
+    }
+}
+
+/// The number the values make written side by side, e.g. 1, 16 makes 116.
+fn read_number(values: impl Iterator<Item = u64>) -> u64 {
+    values.fold(0, |number, value| {
+        let mut shift = 10;
+        while shift <= value {
+            shift *= 10;
+        }
+        number.saturating_mul(shift).saturating_add(value)
+    })
+}
+
+/// The arrows of an arrow sum puzzle.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Arrows {
+    arrows: Vec<Arrow>,
+}
+
+impl Arrows {
+    pub fn new(arrows: Vec<Arrow>) -> Self {
+        Self { arrows }
+    }
+
+    /// Reads arrows written a line each, as the circle's cells, `=`, then the cells along the arrow from the circle, like
+    ///
+    /// ```text
+    /// r1c1 = r1c2 r1c3 r2c4
+    /// r5c5 r5c6 = r6c7, r7c7
+    /// ```
+    ///
+    /// Rows and cols count from 1, and cells are separated by whitespace or commas.
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(input: &str) -> Result<Self, SudokuError> {
+        let mut arrows = Vec::new();
+        for (line, text) in input.lines().enumerate() {
+            if text.trim().is_empty() || text.trim_start().starts_with('#') {
+                continue;
+            }
+            let Some((circle_text, cells_text)) = text.split_once('=') else {
+                return Err(SudokuError::UnknownArrow { line: line + 1, input: text.trim().to_string() });
+            };
+            let circle = parse_cell_names(circle_text, line + 1, 1)?;
+            let cells = parse_cell_names(cells_text, line + 1, circle_text.chars().count() + 2)?;
+            arrows.push(Arrow::try_new(circle, cells)?);
+        }
+        Ok(Self::new(arrows))
+    }
+
+    /// Writes the arrows in the form `parse` reads.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for arrow in self.arrows.iter() {
+            let names = |cells: &[Location]| cells.iter().map(|&location| cell_name(location)).collect::<Vec<String>>().join(" ");
+            text += &format!("{} = {}\n", names(&arrow.circle), names(&arrow.cells));
+        }
+        text
+    }
+
+    pub fn arrows(&self) -> &[Arrow] {
+        &self.arrows
+    }
+}
+
+impl Constraint for Arrows {
+    fn name(&self) -> &str {
+        "arrows"
+    }
+
+    fn cells(&self, _board: &Board) -> Vec<Location> {
+        self.arrows.iter().flat_map(|arrow| arrow.circle.iter().chain(arrow.cells.iter())).copied().collect()
+    }
+
+    fn propagate(&self, board: &mut Board) -> Option<bool> {
+        let mut found_something = false;
+        for arrow in self.arrows.iter() {
+            found_something |= arrow.propagate(board)?;
+        }
+        Some(found_something)
+    }
+
+    fn has_contradiction(&self, board: &Board) -> bool {
+        self.arrows.iter().any(|arrow| arrow.is_broken(board))
+    }
+
+    fn conflicting_cells(&self, board: &Board) -> Vec<Location> {
+        self.arrows
+            .iter()
+            .filter(|arrow| arrow.is_broken(board))
+            .flat_map(|arrow| arrow.circle.iter().chain(arrow.cells.iter()))
+            .copied()
+            .filter(|&location| board.get(location).get_single_index().is_some())
+            .collect()
+    }
+}
+
+impl Board {
+    /// For each cell in row-major order, the mark the terminal drawing puts in it for arrows:
+    /// `○` in a circle, and otherwise an arrow pointing away from the cell before it on its arrow.
+    pub(crate) fn arrow_marks(&self) -> Vec<Option<char>> {
+        let geometry = self.geometry();
+        let mut marks = vec![None; geometry.num_cells()];
+        for arrow in self.constraints_of::<Arrows>().flat_map(|arrows| arrows.arrows.iter()) {
+            for &location in arrow.circle.iter() {
+                if let Some(cell) = geometry.index(location) {
+                    marks[cell] = Some(CIRCLE);
+                }
+            }
+            //The first cell along the arrow points away from the circle cell it touches.
+            let from = arrow.circle.iter().copied().find(|&location| match (location, arrow.cells[0]) {
+                (Location::Valid(r1, c1), Location::Valid(r2, c2)) => r1.abs_diff(r2).max(c1.abs_diff(c2)) == 1,
+                _ => false,
+            });
+            let previous = from.into_iter().chain(arrow.cells.iter().copied());
+            for (previous, location) in previous.zip(arrow.cells.iter().copied()) {
+                let (Location::Valid(r1, c1), Location::Valid(r2, c2), Some(cell)) = (previous, location, geometry.index(location)) else { continue };
+                if marks[cell].is_none() {
+                    marks[cell] = Some(direction_mark(r2.cmp(&r1), c2.cmp(&c1)));
+                }
+            }
+        }
+        marks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+
+    /// The cell's candidates, counting from 1.
+    fn candidates(board: &Board, r: usize, c: usize) -> Vec<usize> {
+        board.get(Location::Valid(r, c)).possibilities.iter().enumerate().filter(|(_, &possible)| possible).map(|(digit, _)| digit + 1).collect()
+    }
+
+    #[test]
+    fn parse_and_to_text_round_trip() {
+        let arrows = Arrows::parse("r1c1 = r1c2 r1c3 r2c4\n# two-digit circle\nr5c5 r5c6 = r6c7, r7c7\n").unwrap();
+        assert_eq!(arrows.arrows()[1].circle(), &[Location::Valid(4, 4), Location::Valid(4, 5)]);
+        assert_eq!(arrows.to_text(), "r1c1 = r1c2 r1c3 r2c4\nr5c5 r5c6 = r6c7 r7c7\n");
+        assert_eq!(Arrows::parse(&arrows.to_text()).unwrap(), arrows);
+    }
+
+    #[test]
+    fn propagation_bounds_the_circle_and_the_cells() {
+        let mut board = Board::new(3, 3);
+        let arrows = Arrows::parse("r1c1 = r1c2 r1c3\nr5c5 r5c6 = r6c7 r7c7 r8c7").unwrap();
+        board.add_constraint(arrows.clone());
+        assert_eq!(arrows.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 0, 0), vec![2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(candidates(&board, 0, 1), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        //Three cells add up to at most 27.
+        assert_eq!(candidates(&board, 4, 4), vec![1, 2]);
+        assert_eq!(candidates(&board, 4, 5).len(), 9);
+
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(9, 3));
+        assert_eq!(arrows.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 0, 1), vec![1, 2, 3]);
+        assert_eq!(candidates(&board, 0, 2), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn wrong_sum_conflicts() {
+        let mut board = Board::new(3, 3);
+        let arrows = Arrows::parse("r1c1 = r1c2 r1c3").unwrap();
+        board.add_constraint(arrows.clone());
+        for (c, digit) in [(0, 4), (1, 1), (2, 3)] {
+            board.set(Location::Valid(0, c), Cell::new_single_digit(9, digit));
+        }
+        assert!(arrows.has_contradiction(&board));
+        assert_eq!(arrows.conflicting_cells(&board), vec![Location::Valid(0, 0), Location::Valid(0, 1), Location::Valid(0, 2)]);
+    }
+
+    #[test]
+    fn parse_reports_the_line_without_an_equals_sign() {
+        let error = Arrows::parse("r1c1 = r1c2\n\nr5c5 r5c6 r6c7\n").unwrap_err();
+        assert_eq!(error, SudokuError::UnknownArrow { line: 3, input: "r5c5 r5c6 r6c7".to_string() });
+    }
+}
@@ -1,15 +1,36 @@
-use std::fmt::Display;
+use std::{any::Any, fmt::{Debug, Display}, sync::Arc};
 
 use crate::{
     boolean_operation::BooleanOperation,
     cell::Cell,
+    constraint::{BoxConstraint, ColConstraint, Constraint, RowConstraint},
     digit_iterator::DigitIterator,
     digit_set::DigitSet,
+    error::SudokuError,
+    geometry::Geometry,
     index_iterator::IndexIterator,
-    location::Location
+    location::Location,
+    provenance::{Provenance, Technique},
+    render::RenderOptions,
+    symbols::SymbolSet
 };
 
-const DEBUG: u8 = 0;
+/// Prints each step of `fast_solve` and `solve`.
+const DEBUG: bool = false;
+/// Also prints every set `solve` checks, and every candidate it removes.
+const VERBOSE_DEBUG: bool = false;
+
+/// The largest number of cells per row, col, and box that a `Board` supports.
+pub const MAX_SIZE: usize = 64;
+
+/// What happened while `fast_solve_with_stats` was running.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    /// Whether the board ended up solved.
+    pub solved: bool,
+    /// How many times the solver had to guess a digit (including guesses it later backtracked from).
+    pub guesses: usize,
+}
 
 /// Represents the state of a Sudoku board in the process of being solved,
 /// from the initial given state to a fully solved board.
@@ -19,103 +40,335 @@ pub struct Board {
     cols_per_box: usize,
     cells_per_set: usize,
     tiles: Vec<Cell>,
+    geometry: Arc<Geometry>,
+    symbols: Arc<SymbolSet>,
+    constraints: Arc<[Arc<dyn Constraint>]>,
 }
 
 impl Board {
+    /// Creates a blank board whose boxes are `rows_per_box` x `cols_per_box`.
+    /// Panics if there would be more than `MAX_SIZE` cells per set; see `try_new`.
     pub fn new(rows_per_box: usize, cols_per_box: usize) -> Self {
-        let cells_per_set = rows_per_box*cols_per_box;
+        match Self::try_new(rows_per_box, cols_per_box) {
+            Ok(board) => board,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Creates a blank board whose boxes are `rows_per_box` x `cols_per_box`,
+    /// or an error if there would be no cells or more than `MAX_SIZE` cells per set.
+    pub fn try_new(rows_per_box: usize, cols_per_box: usize) -> Result<Self, SudokuError> {
+        let cells_per_set = rows_per_box.saturating_mul(cols_per_box);
+        if cells_per_set == 0 || cells_per_set > MAX_SIZE {
+            return Err(SudokuError::UnsupportedShape { rows_per_box, cols_per_box });
+        }
         let total_num_cells = cells_per_set*cells_per_set;
         let mut tiles = Vec::with_capacity(total_num_cells);
         for _ in 0..total_num_cells {
             tiles.push(Cell::new(cells_per_set));
         }
-        return Self {
+        Ok(Self {
             rows_per_box,
             cols_per_box,
             cells_per_set,
             tiles,
+            geometry: Geometry::shared(rows_per_box, cols_per_box),
+            symbols: Arc::new(SymbolSet::digits(cells_per_set)?),
+            constraints: Arc::new([Arc::new(RowConstraint), Arc::new(ColConstraint), Arc::new(BoxConstraint)]),
+        })
+    }
+
+    /// Creates a board from rows of chars, where `.` is a blank cell and digits are read using `DIGIT_CHARS`.
+    /// The box shape is inferred from the number of rows (see `box_shape`).
+    /// Panics on invalid input; see `try_from_chars`.
+    pub fn from_chars<R: AsRef<[char]>>(input: &[R]) -> Self {
+        match Self::try_from_chars(input) {
+            Ok(board) => board,
+            Err(error) => panic!("{error}"),
         }
     }
 
-    pub fn from_chars(input: &[[char; 9]; 9]) -> Self {
-        let mut tiles: Vec<Cell> = Vec::with_capacity(81);
-        for row in input.iter().take(9) {
-            for &digit in row.iter().take(9) {
-                if digit == '.' {
-                    tiles.push(Cell::new(9));
+    /// Same as `from_chars`, but returns an error if the input isn't square, is too large, or has an invalid char.
+    pub fn try_from_chars<R: AsRef<[char]>>(input: &[R]) -> Result<Self, SudokuError> {
+        let cells_per_set = input.len();
+        let (rows_per_box, cols_per_box) = box_shape(cells_per_set);
+        let mut board = Self::try_new(rows_per_box, cols_per_box)?;
+        for (r, row) in input.iter().enumerate() {
+            let row = row.as_ref();
+            if row.len() != cells_per_set {
+                return Err(SudokuError::WrongRowLength { row: r, expected: cells_per_set, found: row.len() });
+            }
+            for (c, &digit) in row.iter().enumerate() {
+                if digit != '.' {
+                    board.try_set(Location::Valid(r, c), Cell::try_new_single_char(cells_per_set, digit)?.with_provenance(Provenance::Given))?;
                 }
-                else {
-                    tiles.push(Cell::new_single_char(9, digit));
+            }
+        }
+
+        Ok(board)
+    }
+
+    /// Returns the precomputed house and peer tables for this board's shape and constraints.
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    /// The rules the board's digits follow, which are `RowConstraint`, `ColConstraint`, and `BoxConstraint` to begin with.
+    pub fn constraints(&self) -> &[Arc<dyn Constraint>] {
+        &self.constraints
+    }
+
+    /// Returns the board's first constraint of the given type, if it has one.
+    pub fn constraint<C: Constraint>(&self) -> Option<&C> {
+        self.constraints_of().next()
+    }
+
+    /// Returns every constraint of the given type the board has.
+    pub fn constraints_of<C: Constraint>(&self) -> impl Iterator<Item = &C> {
+        self.constraints.iter().filter_map(|constraint| (constraint.as_ref() as &dyn Any).downcast_ref::<C>())
+    }
+
+    /// Adds a rule the board's digits must follow.
+    /// Panics if one of its houses doesn't fit the board; see `try_add_constraint`.
+    pub fn add_constraint<C: Constraint + 'static>(&mut self, constraint: C) {
+        if let Err(error) = self.try_add_constraint(constraint) {
+            panic!("{error}");
+        }
+    }
+
+    /// Adds a rule the board's digits must follow, or returns an error if one of its cells isn't on the board,
+    /// or one of its houses has the same cell twice or more cells than there are digits.
+    pub fn try_add_constraint<C: Constraint + 'static>(&mut self, constraint: C) -> Result<(), SudokuError> {
+        self.try_add_shared_constraint(Arc::new(constraint))
+    }
+
+    /// Same as `try_add_constraint`, for a constraint that's already shared, e.g. one from another board's `constraints`.
+    pub fn try_add_shared_constraint(&mut self, constraint: Arc<dyn Constraint>) -> Result<(), SudokuError> {
+        let mut constraints = self.constraints.to_vec();
+        constraints.push(constraint);
+        self.try_set_constraints(constraints)
+    }
+
+    /// Removes every constraint of the given type from the board, e.g. to turn a variant off, and returns how many there were.
+    pub fn remove_constraints_of<C: Constraint>(&mut self) -> usize {
+        let mut constraints = self.constraints.to_vec();
+        constraints.retain(|constraint| !(constraint.as_ref() as &dyn Any).is::<C>());
+        let removed = self.constraints.len() - constraints.len();
+        if removed > 0 {
+            //The houses that are left already fit the board.
+            if let Err(error) = self.try_set_constraints(constraints) {
+                panic!("{error}");
+            }
+        }
+        removed
+    }
+
+    /// Replaces every constraint of the board, rebuilding its houses.
+    pub(crate) fn try_set_constraints(&mut self, constraints: Vec<Arc<dyn Constraint>>) -> Result<(), SudokuError> {
+        let mut houses = Vec::new();
+        for constraint in constraints.iter() {
+            for house in constraint.houses(self) {
+                if house.len() > self.cells_per_set {
+                    return Err(SudokuError::HouseTooLarge { expected: self.cells_per_set, found: house.len() });
                 }
+                let mut cells = Vec::with_capacity(house.len());
+                for location in house {
+                    let cell = self.geometry.index(location).ok_or(SudokuError::InvalidLocation(location))?;
+                    if cells.contains(&cell) {
+                        return Err(SudokuError::RepeatedCell(location));
+                    }
+                    cells.push(cell);
+                }
+                houses.push(cells);
+            }
+            for location in constraint.cells(self) {
+                self.geometry.index(location).ok_or(SudokuError::InvalidLocation(location))?;
             }
         }
 
-        Self {
-            rows_per_box: 3,
-            cols_per_box: 3,
-            cells_per_set: 9,
-            tiles,
+        self.geometry = Arc::new(Geometry::with_houses(self.rows_per_box, self.cols_per_box, houses));
+        self.constraints = constraints.into();
+        Ok(())
+    }
+
+    /// Returns true iff the two locations are different cells sharing a house (a row, col, or box on a classic board).
+    pub fn sees(&self, a: Location, b: Location) -> bool {
+        match (self.geometry.index(a), self.geometry.index(b)) {
+            (Some(a), Some(b)) => self.geometry.sees(a, b),
+            _ => false,
         }
     }
 
+    /// Returns the locations of every cell that sees both of the given locations.
+    pub fn common_peers(&self, a: Location, b: Location) -> Vec<Location> {
+        match (self.geometry.index(a), self.geometry.index(b)) {
+            (Some(a), Some(b)) => self.geometry.common_peers(a, b).into_iter().map(|cell| self.geometry.location(cell)).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns a reference to a Cell, or an error if the location isn't on the board.
+    pub fn try_get(&self, location: Location) -> Result<&Cell, SudokuError> {
+        let index = self.geometry.index(location).ok_or(SudokuError::InvalidLocation(location))?;
+        Ok(&self.tiles[index])
+    }
+
+    /// Returns a mutable reference to a Cell, or an error if the location isn't on the board.
+    pub fn try_get_mut(&mut self, location: Location) -> Result<&mut Cell, SudokuError> {
+        let index = self.geometry.index(location).ok_or(SudokuError::InvalidLocation(location))?;
+        Ok(&mut self.tiles[index])
+    }
+
+    /// Replaces the specified Cell with the input Cell, or returns an error if
+    /// the location isn't on the board or the Cell is the wrong size for it.
+    pub fn try_set(&mut self, location: Location, input: Cell) -> Result<(), SudokuError> {
+        if input.possibilities.len() != self.cells_per_set {
+            return Err(SudokuError::WrongCellSize { expected: self.cells_per_set, found: input.possibilities.len() });
+        }
+        *self.try_get_mut(location)? = input;
+        Ok(())
+    }
+
     /// Returns a reference to a Cell
+    /// Panics if the location isn't on the board; see `try_get`.
     pub fn get(&self, location: Location) -> &Cell {
-        if let Location::Valid(r, c) = location {
-            &self.tiles[r*self.cells_per_set + c]
-        }
-        else {
-            panic!("{location} given to .get()");
+        match self.try_get(location) {
+            Ok(cell) => cell,
+            Err(error) => panic!("{error} given to .get()"),
         }
     }
 
     /// Returns a mutable reference to a Cell
+    /// Panics if the location isn't on the board; see `try_get_mut`.
     pub fn get_mut(&mut self, location: Location) -> &mut Cell {
-        if let Location::Valid(r, c) = location {
-            &mut self.tiles[r*self.cells_per_set + c]
-        }
-        else {
-            panic!("{location} given to .get_mut()");
+        match self.try_get_mut(location) {
+            Ok(cell) => cell,
+            Err(error) => panic!("{error} given to .get_mut()"),
         }
     }
 
     /// Replaces the specified Cell with the input Cell
+    /// Panics if the location isn't on the board or the Cell is the wrong size; see `try_set`.
     pub fn set(&mut self, location: Location, input: Cell) {
-        if let Location::Valid(r, c) = location {
-            self.tiles[r*self.cells_per_set + c] = input;
+        if let Err(error) = self.try_set(location, input) {
+            panic!("{error} given to .set()");
         }
-        else {
-            panic!("{location} given to .set()");
+    }
+
+    /// Places the digit in the cell as a player's entry, or returns an error if the cell is a given,
+    /// the location isn't on the board, or the digit is too large.
+    pub fn try_enter(&mut self, location: Location, digit: usize) -> Result<(), SudokuError> {
+        if self.try_get(location)?.is_given() {
+            return Err(SudokuError::GivenCell(location));
+        }
+        let cell = Cell::try_new_single_digit(self.cells_per_set, digit)?.with_provenance(Provenance::Player);
+        self.try_set(location, cell)
+    }
+
+    /// Places the digit in the cell as a player's entry.
+    /// Panics if the cell is a given or on invalid input; see `try_enter`.
+    pub fn enter(&mut self, location: Location, digit: usize) {
+        if let Err(error) = self.try_enter(location, digit) {
+            panic!("{error} given to .enter()");
+        }
+    }
+
+    /// Marks every solved cell as a given, e.g. after setting up a puzzle with `set`.
+    pub fn mark_givens(&mut self) {
+        for cell in self.tiles.iter_mut().filter(|cell| cell.solved) {
+            cell.provenance = Provenance::Given;
         }
     }
 
     /// Same logic as `solve()`, but combines loops to make it faster (though less readable)
     pub fn fast_solve(&mut self) {
+        self.fast_solve_with_stats();
+    }
+
+    /// Same as `fast_solve()`, but also reports whether it worked and how many guesses it needed.
+    pub fn fast_solve_with_stats(&mut self) -> SolveStats {
         let mut states_before_guesses = Vec::new();
+        let mut stats = SolveStats::default();
         //While there's still progress to be made, keep looping.
         loop {
             //Find hidden and naked singles, and check if the puzzle is unsolvable.
-            let solvable = self.fast_reduction_loop().is_some();
+            let solvable = self.fast_reduction_loop(true).is_some();
             if solvable && self.is_solved() {
-                return;
+                stats.solved = true;
+                return stats;
             }
-            else if !solvable || self.has_contradiction() {
-                if self.backtrack(&mut states_before_guesses).is_none() {
-                    break;
-                }
+            else if (!solvable || self.has_contradiction()) && self.backtrack(&mut states_before_guesses).is_none() {
+                break;
             }
 
             if self.guess_or_backtrack(&mut states_before_guesses).is_none() {
                 break;
             }
+            stats.guesses += 1;
 
             if self.is_solved() {
+                stats.solved = true;
                 break;
             }
         }
 
-        if DEBUG > 0 {
+        if DEBUG {
             println!("{self}");
         }
+        stats
+    }
+
+    /// Counts the solutions of the board, stopping once `limit` have been found.
+    /// So `count_solutions(2) == 1` means the puzzle has exactly one solution.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions(limit).len()
+    }
+
+    /// Finds solutions of the board, stopping once `limit` have been found.
+    /// Each guessed digit records how many guesses deep it was in its provenance.
+    pub(crate) fn solutions(&self, limit: usize) -> Vec<Board> {
+        self.solutions_within(limit, usize::MAX).unwrap_or_default()
+    }
+
+    /// Same as `solutions`, but gives up and returns `None` after exploring `max_boards` boards
+    /// without either finding `limit` solutions or running out of boards to explore.
+    pub(crate) fn solutions_within(&self, limit: usize, max_boards: usize) -> Option<Vec<Board>> {
+        let mut solutions = Vec::new();
+        //Each board to explore, with how many guesses deep it is.
+        let mut unexplored = vec![(self.clone(), 0)];
+        let mut explored = 0;
+        while let Some((mut board, depth)) = unexplored.pop() {
+            if solutions.len() >= limit {
+                break;
+            }
+            if explored == max_boards {
+                return None;
+            }
+            explored += 1;
+            if board.fast_reduction_loop(true).is_none() || board.has_contradiction() {
+                continue;
+            }
+            if board.is_solved() {
+                solutions.push(board);
+                continue;
+            }
+
+            //Branch on every possibility of the unsolved cell with the fewest possibilities.
+            let guess_cell = (0..board.tiles.len())
+                .filter(|&cell_index| !board.tiles[cell_index].solved)
+                .min_by_key(|&cell_index| board.tiles[cell_index].num_possibilities());
+            if let Some(cell_index) = guess_cell {
+                for (digit, &possible) in board.tiles[cell_index].possibilities.iter().enumerate().rev() {
+                    if possible {
+                        let mut guess = board.clone();
+                        guess.tiles[cell_index] = Cell::new_single_digit(self.cells_per_set, digit).with_provenance(Provenance::Guess { depth: depth + 1 });
+                        unexplored.push((guess, depth + 1));
+                    }
+                }
+            }
+        }
+
+        Some(solutions)
     }
 
     fn guess_or_backtrack(&mut self, states_before_guesses: &mut Vec<Board>) -> Option<()> {
@@ -124,7 +377,7 @@ impl Board {
         //Keep backtracking until we find a valid guess
         while guess_location.is_none() || guess_index.is_none() {
             //Try to find the (unsolved) cell with the fewest possibilities
-            let mut min_possibilities = 10;
+            let mut min_possibilities = self.cells_per_set + 1;
             for location in self.iter_indices(DigitSet::All(self.cells_per_set)) {
                 let cell = self.get(location);
                 let current_possibilities = cell.num_possibilities();
@@ -157,13 +410,11 @@ impl Board {
                 self.make_guess(states_before_guesses, location, index);
             }
             else {
-                if DEBUG > 0 {
+                if DEBUG {
                     println!("Couldn't find a guess; backtracking.");
                 }
                 //Couldn't find a guess, need to backtrack
-                if self.backtrack(states_before_guesses).is_none() {
-                    return None;
-                }
+                self.backtrack(states_before_guesses)?;
             }
         }
 
@@ -182,8 +433,9 @@ impl Board {
         states_before_guesses.push(self.clone());
 
         //Make a solved version of the cell and put it into self
-        let solved = Cell::new_single_digit(self.cells_per_set, index);
-        if DEBUG > 0 {
+        let depth = states_before_guesses.len();
+        let solved = Cell::new_single_digit(self.cells_per_set, index).with_provenance(Provenance::Guess { depth });
+        if DEBUG {
             println!("Guessing {solved} at {location} which had {num_possibilities} possibilities.");
             println!("{self}");
         }
@@ -196,202 +448,150 @@ impl Board {
             Some(())
         }
         else {
-            if DEBUG > 0 {
+            if DEBUG {
                 println!("No more states to backtrack to. Puzzle is unsolvable.");
             }
             None
         }
     }
 
-    fn fast_reduction_loop(&mut self) -> Option<()> {
+    /// Finds naked singles (and hidden singles too if `find_hidden_singles` is true) until nothing else can be found.
+    /// Returns `None` if the puzzle is unsolvable in its current state.
+    pub(crate) fn fast_reduction_loop(&mut self, find_hidden_singles: bool) -> Option<()> {
         let set_size = self.cells_per_set;
-        let rows_per_box = self.rows_per_box;
-        let cols_per_box = self.cols_per_box;
-        let mut found_something = Some(true);
+        let geometry = Arc::clone(&self.geometry);
+        let num_houses = geometry.houses().len();
+        let mut found_something = true;
 
-        while found_something.unwrap_or(false) {
-            if DEBUG > 0 {
+        while found_something {
+            if DEBUG {
                 println!("{self}");
             }
-            found_something = Some(false);
-            //Keep track of which digits have been used, and where
-            let mut used_rows = vec![vec![false; set_size]; set_size];
-            let mut used_cols = vec![vec![false; set_size]; set_size];
-            let mut used_boxes = vec![vec![false; set_size]; set_size];
+            found_something = false;
+            //Keep track of which digits have been used in each house
+            let mut used = vec![vec![false; set_size]; num_houses];
 
             //Find the location of each solved digit.
-            for location in IndexIterator::new(DigitSet::All(set_size), set_size) {
-                if let Location::Valid(row, col) = location {
-                    let cell = self.get(location);
-                    if cell.solved {
-                        let box_index = rows_per_box * (row / rows_per_box) + (col / cols_per_box);
-                        let value = cell.get_single_index().unwrap();
-                        used_rows[row][value] = true;
-                        used_cols[col][value] = true;
-                        used_boxes[box_index][value] = true;
+            for (cell_index, cell) in self.tiles.iter().enumerate() {
+                if cell.solved {
+                    let value = cell.get_single_index()?;
+                    for &house in geometry.houses_of(cell_index) {
+                        used[house][value] = true;
                     }
                 }
-                else {
-                    panic!("{location} when finding location of solved digits.");
-                }
             }
 
             //Remove the possibilities of solved digits in each row, col, and box it's in.
-            for location in IndexIterator::new(DigitSet::All(set_size), set_size) {
-                if let Location::Valid(row, col) = location {
-                    let cell = self.get_mut(location);
-                    if !cell.solved {
-                        let box_index = rows_per_box * (row / rows_per_box) + (col / cols_per_box);
-                        for digit in 0..set_size {
-                            if cell.possibilities[digit] && (used_rows[row][digit] || used_cols[col][digit] || used_boxes[box_index][digit]) {
-                                cell.possibilities[digit] = false;
-                                found_something = Some(true);
-                            }
+            for (cell_index, cell) in self.tiles.iter_mut().enumerate() {
+                if !cell.solved {
+                    let houses = geometry.houses_of(cell_index);
+                    for (digit, possible) in cell.possibilities.iter_mut().enumerate() {
+                        if *possible && houses.iter().any(|&house| used[house][digit]) {
+                            *possible = false;
+                            found_something = true;
                         }
+                    }
 
-                        if cell.check_newly_solved() {
-                            found_something = Some(true);
+                    if cell.check_newly_solved() {
+                        found_something = true;
+                        cell.provenance = Provenance::Technique(Technique::NakedSingle);
 
-                            let value = cell.get_single_index().unwrap();
-                            used_rows[row][value] = true;
-                            used_cols[col][value] = true;
-                            used_boxes[box_index][value] = true;
+                        let value = cell.get_single_index()?;
+                        for &house in houses {
+                            used[house][value] = true;
                         }
+                    }
 
-                        if !cell.solved && cell.num_possibilities() == 0 {
-                            found_something = None;
-                            if DEBUG > 0 {
-                                println!("Unsolvable.");
-                            }
-                            break;
+                    if !cell.solved && cell.num_possibilities() == 0 {
+                        if DEBUG {
+                            println!("Unsolvable.");
                         }
+                        return None;
                     }
                 }
-                else {
-                    panic!("{location} when removing possibilities of solved digits.");
-                }
             }
 
-            //Look for hidden singles for each digit
-            for needed_digit in 0..set_size {
-                let mut found_digit = false;
-                for row in 0..set_size {
-                    if !used_rows[row][needed_digit] {
-                        let mut location = Location::Invalid;
-                        for col in 0..set_size {
-                            let cell = self.get(Location::Valid(row, col));
-                            if cell.possibilities[needed_digit] {
-                                if location == Location::Invalid {
-                                    location = Location::Valid(row, col);
-                                }
-                                else {
-                                    location = Location::Duplicate;
-                                    break;
-                                }
-                            }
-                        }
-
-                        if location != Location::Invalid && location != Location::Duplicate {
-                            let solved_cell = Cell::new_single_digit(set_size, needed_digit);
-                            self.set(location, solved_cell);
-                            found_something = Some(true);
-                            found_digit = true;
-                            break;
-                        }
-                        else if location == Location::Invalid {
-                            if DEBUG > 0 {
-                                println!("Unsolvable.");
-                            }
-                            found_something = None;
-                        }
+            //Look for hidden singles for each digit, in every row, col, and box.
+            //Boxes count like they always have in `solve`'s `check_single_location`, which this loop is meant to match;
+            //before the house tables, the box pass here was commented out because it worked out box cells wrongly.
+            let digits_to_check = if find_hidden_singles { set_size } else { 0 };
+            for needed_digit in 0..digits_to_check {
+                for (house, used_digits) in geometry.houses().iter().zip(used.iter()) {
+                    //A house with fewer cells than digits doesn't need every digit.
+                    if used_digits[needed_digit] || house.len() < set_size {
+                        continue;
                     }
-                }
-                if found_digit {
-                    continue;
-                }
 
-                for col in 0..set_size {
-                    if !used_cols[col][needed_digit] {
-                        let mut location = Location::Invalid;
-                        for row in 0..set_size {
-                            let cell = self.get(Location::Valid(row, col));
-                            if cell.possibilities[needed_digit] {
-                                if location == Location::Invalid {
-                                    location = Location::Valid(row, col);
-                                }
-                                else {
-                                    location = Location::Duplicate;
-                                    break;
-                                }
+                    let mut location = Location::Invalid;
+                    for &cell_index in house {
+                        if self.tiles[cell_index].possibilities[needed_digit] {
+                            if location == Location::Invalid {
+                                location = geometry.location(cell_index);
+                            }
+                            else {
+                                location = Location::Duplicate;
+                                break;
                             }
                         }
+                    }
 
-                        if location != Location::Invalid && location != Location::Duplicate {
-                            let solved_cell = Cell::new_single_digit(set_size, needed_digit);
-                            self.set(location, solved_cell);
-                            found_something = Some(true);
-                            found_digit = true;
-                            break;
-                        }
-                        else if location == Location::Invalid {
-                            if DEBUG > 0 {
-                                println!("Unsolvable.");
-                            }
-                            found_something = None;
+                    if location == Location::Invalid {
+                        if DEBUG {
+                            println!("Unsolvable.");
                         }
+                        return None;
+                    }
+                    else if location != Location::Duplicate {
+                        let solved_cell = Cell::new_single_digit(set_size, needed_digit).with_provenance(Provenance::Technique(Technique::HiddenSingle));
+                        self.set(location, solved_cell);
+                        found_something = true;
+                        //The used tables are stale now, so move on to the next digit.
+                        break;
                     }
                 }
-                if found_digit {
-                    continue;
-                }
-
-                // for box_index in 0..set_size {
-                //     if !used_boxes[box_index][needed_digit] {
-                //         let mut location = Location::Invalid;
-                //         for intra_box_index in 0..set_size {
-                //             let row = intra_box_index / self.cols_per_box + self.cols_per_box * (box_index / self.cols_per_box);
-                //             let col = intra_box_index % self.cols_per_box + self.cols_per_box * (box_index % self.cols_per_box);
-                //             let cell = self.get(Location::Valid(row, col));
-                //             if cell.possibilities[needed_digit] {
-                //                 if location == Location::Invalid {
-                //                     location = Location::Valid(row, col);
-                //                 }
-                //                 else {
-                //                     location = Location::Duplicate;
-                //                     break;
-                //                 }
-                //             }
-                //         }
-
-                //         if location != Location::Invalid && location != Location::Duplicate {
-                //             let solved_cell = Cell::new_single_digit(set_size, needed_digit);
-                //             self.set(location, solved_cell);
-                //             found_something = Some(true);
-                //             break;
-                //         }
-                //         else if location == Location::Invalid {
-                //             if DEBUG > 0 {
-                //                 println!("Unsolvable.");
-                //             }
-                //             found_something = None;
-                //         }
-                //     }
-                // }
-            }
-        }
-
-        if found_something.is_none() {
-            if DEBUG > 0 {
-                println!("Unsolvable puzzle.");
             }
-            return None;
+
+            found_something |= self.propagate_constraints()?;
         }
 
         Some(())
     }
 
+    /// Lets every constraint remove candidates once.
+    /// Returns true if any did, and `None` if one found the puzzle unsolvable in its current state.
+    pub(crate) fn propagate_constraints(&mut self) -> Option<bool> {
+        let constraints = Arc::clone(&self.constraints);
+        let mut found_something = false;
+        for constraint in constraints.iter() {
+            found_something |= constraint.propagate(self)?;
+        }
+        Some(found_something)
+    }
+
+    /// Returns true iff a house has a digit twice, or a constraint is otherwise broken.
     pub fn has_contradiction(&mut self) -> bool {
-        self.for_sets(Self::has_contradiction_set, BooleanOperation::OrLazy).unwrap()
+        self.for_sets(Self::has_contradiction_set, BooleanOperation::OrLazy).unwrap_or(true)
+            || self.constraints.iter().any(|constraint| constraint.has_contradiction(self))
+    }
+
+    /// Returns the locations of every cell that shares a house with another cell down to the same single digit,
+    /// or that a constraint says breaks its rule (see `Constraint::conflicting_cells`), in row-major order.
+    pub fn conflicting_cells(&self) -> Vec<Location> {
+        let mut conflicting = vec![false; self.tiles.len()];
+        for (cell_index, cell) in self.tiles.iter().enumerate() {
+            let Some(digit) = cell.get_single_index() else { continue };
+            if self.geometry.peers(cell_index).iter().any(|&peer| self.tiles[peer].get_single_index() == Some(digit)) {
+                conflicting[cell_index] = true;
+            }
+        }
+        for constraint in self.constraints.iter() {
+            for location in constraint.conflicting_cells(self) {
+                if let Some(cell_index) = self.geometry.index(location) {
+                    conflicting[cell_index] = true;
+                }
+            }
+        }
+        (0..self.tiles.len()).filter(|&cell_index| conflicting[cell_index]).map(|cell_index| self.geometry.location(cell_index)).collect()
     }
 
     fn has_contradiction_set(&mut self, set: DigitSet) -> Option<bool> {
@@ -413,19 +613,19 @@ impl Board {
     pub fn solve(&mut self) {
         let unsolvable = self.reduce_and_check_singles_loop().is_none();
         if unsolvable {
-            if DEBUG > 0 {
+            if DEBUG {
                 println!("Unsolvable puzzle.");
             }
             return;
         }
 
-        if DEBUG > 0 {
+        if DEBUG {
             println!("{self}");
         }
 
         //Check if the board is solved
         if self.is_solved() {
-            if DEBUG > 0 {
+            if DEBUG {
                 println!("{self}");
                 println!("Solved!");
             }
@@ -437,18 +637,16 @@ impl Board {
             self.guess_or_backtrack(&mut states_before_guesses);
             //Reduce possibilities as much as possible
             let unsolvable = self.reduce_and_check_singles_loop().is_none();
-            if DEBUG > 0 {
+            if DEBUG {
                 println!("After reduction:\n{self}");
             }
-            if unsolvable {
-                if DEBUG > 0 {
-                    println!("Unsolvable in its current state.");
-                }
+            if unsolvable && DEBUG {
+                println!("Unsolvable in its current state.");
             }
 
             //And lastly, check if self is solved yet
             if !unsolvable && self.is_solved() {
-                if DEBUG > 0 {
+                if DEBUG {
                     println!("{self}");
                     println!("Solved!");
                 }
@@ -457,35 +655,18 @@ impl Board {
         }
     }
     
-    /// Apply the given function to every row, col, and box. Returns the `BooleanOperation`'s 'combine' of each value.
+    /// Apply the given function to every house of every constraint (the rows, cols, and boxes of a classic board).
+    /// Returns the `BooleanOperation`'s 'combine' of each value.
     /// Iff `operation` is Lazy, the function might not be evaluated for every set.
-    pub fn for_sets<F>(&mut self, func: F, operation: BooleanOperation) -> Option<bool>
+    pub(crate) fn for_sets<F>(&mut self, func: F, operation: BooleanOperation) -> Option<bool>
     where
         F: Fn(&mut Self, DigitSet) -> Option<bool>,
     {
         let mut result = Some(operation.initial());
 
-        for r in 0..self.cells_per_set {
-            let current_value = func(self, DigitSet::Row(self.cells_per_set, r));
-            let Some(value) = current_value else { return None };
-            result = Some(operation.combine(result.unwrap(), value));
-            if matches!(operation, BooleanOperation::OrLazy | BooleanOperation::AndLazy) && result != Some(operation.initial()) {
-                return result;
-            }
-        }
-
-        for c in 0..self.cells_per_set {
-            let current_value = func(self, DigitSet::Col(self.cells_per_set, c));
-            let Some(value) = current_value else { return None };
-            result = Some(operation.combine(result.unwrap(), value));
-            if matches!(operation, BooleanOperation::OrLazy | BooleanOperation::AndLazy) && result != Some(operation.initial()) {
-                return result;
-            }
-        }
-
-        for b in 0..self.cells_per_set {
-            let current_value = func(self, DigitSet::Box(self.cells_per_set, b));
-            let Some(value) = current_value else { return None };
+        for house in 0..self.geometry.houses().len() {
+            let current_value = func(self, DigitSet::House(self.cells_per_set, house));
+            let value = current_value?;
             result = Some(operation.combine(result.unwrap(), value));
             if matches!(operation, BooleanOperation::OrLazy | BooleanOperation::AndLazy) && result != Some(operation.initial()) {
                 return result;
@@ -495,22 +676,19 @@ impl Board {
         result
     }
 
-    pub fn reduce_and_check_singles_loop(&mut self) -> Option<bool> {
+    pub(crate) fn reduce_and_check_singles_loop(&mut self) -> Option<bool> {
         let mut found_something_ever = false;
         let mut found_something_now = true;
         while found_something_now {
             let found_by_reducing = self.for_sets(Board::reduce_possibilities, BooleanOperation::Or);
             //Don't bother checking singles if the puzzle is definitely unsolvable.
-            if found_by_reducing.is_none() {
-                return None;
-            }
+            let found_by_reducing = found_by_reducing?;
             
-            let found_by_singles = self.for_sets(Board::check_single_location, BooleanOperation::Or);
-            if found_by_singles.is_none() {
-                return None;
-            }
-            
-            found_something_now = found_by_reducing.unwrap() || found_by_singles.unwrap();
+            let found_by_singles = self.for_sets(Board::check_single_location, BooleanOperation::Or)?;
+
+            let found_by_constraints = self.propagate_constraints()?;
+
+            found_something_now = found_by_reducing || found_by_singles || found_by_constraints;
             found_something_ever |= found_something_now;
         }
 
@@ -518,8 +696,8 @@ impl Board {
     }
     
     /// Remove possibilities from the set based on solved digits
-    pub fn reduce_possibilities(&mut self, set: DigitSet) -> Option<bool> {
-        if DEBUG > 1 {
+    pub(crate) fn reduce_possibilities(&mut self, set: DigitSet) -> Option<bool> {
+        if VERBOSE_DEBUG {
             println!("Reducing {set}...");
         }
         //Output value
@@ -533,8 +711,8 @@ impl Board {
             if let Some(solved_cell) = cell.get_single_index() {
                 //Set possbile to false for that digit
                 possible[solved_cell] = false;
-                if DEBUG > 1 {
-                    println!("Can't use {}...", solved_cell + 1);
+                if VERBOSE_DEBUG {
+                    println!("Can't use {}...", self.symbols.char_of(solved_cell).unwrap_or('?'));
                 }
             }
         }
@@ -554,8 +732,11 @@ impl Board {
                 let newly_solved = cell.check_newly_solved();
                 //If so, found_something is true
                 found_something |= newly_solved;
-                if DEBUG > 1 && newly_solved {
-                    println!("Newly solved: {cell} at ({location})!");
+                if newly_solved {
+                    cell.provenance = Provenance::Technique(Technique::NakedSingle);
+                }
+                if VERBOSE_DEBUG && newly_solved {
+                    println!("Newly solved: {} at ({location})!", self.symbol_at(location).unwrap_or('?'));
                 }
             }
         }
@@ -568,21 +749,25 @@ impl Board {
     /// that cell must be 1.
     /// Returns true if if found something, false if it didn't, and `None` if the
     /// puzzle is unsolvable in its current state.
-    pub fn check_single_location(&mut self, set: DigitSet) -> Option<bool> {
-        if DEBUG > 1 {
+    pub(crate) fn check_single_location(&mut self, set: DigitSet) -> Option<bool> {
+        if VERBOSE_DEBUG {
             println!("Checking hidden singles in {set}...");
         }
+        //A house with fewer cells than digits doesn't need every digit.
+        if self.geometry.cells(set).len() < self.cells_per_set {
+            return Some(false);
+        }
         //Check the set for a hidden single of each digit
         for needed_digit in 0..self.cells_per_set {
-            if DEBUG > 1 {
+            if VERBOSE_DEBUG {
                 println!("Checking for {needed_digit} singles...");
             }
             //Stores the *only* possible location for the needed_digit, if it exists.
             let mut possible_digit_location = Some(Location::Invalid);
             //Loop through each position in the set and get a reference to each Cell
             for (location, cell) in self.iter_indices(set).zip(self.iter_digits(set)) {
-                if DEBUG > 1 {
-                    println!("Checking the {cell} at location");
+                if VERBOSE_DEBUG {
+                    println!("Checking the {} at {location}", self.symbol_at(location).unwrap_or(' '));
                 }
                 //If the digit can be needed_digit,
                 if cell.possibilities[needed_digit] {
@@ -610,9 +795,9 @@ impl Board {
                 else {
                     //Otherwise, update the cell (unless it's solved).
                     if !self.get(location).solved {
-                        let solved = Cell::new_single_digit(self.cells_per_set, needed_digit);
+                        let solved = Cell::new_single_digit(self.cells_per_set, needed_digit).with_provenance(Provenance::Technique(Technique::HiddenSingle));
                         self.set(location, solved.clone());
-                        if DEBUG > 1 {
+                        if VERBOSE_DEBUG {
                             println!("Found single {solved} at {location}!");
                             println!("{self}");
                             println!("Reducing after finding digit...");
@@ -629,13 +814,15 @@ impl Board {
         Some(false)
     }
 
-    /// Checks if the given set has the digits 1 through 9 once each.
-    pub fn check_solved_set(&mut self, set: DigitSet) -> Option<bool> {
+    /// Checks if the given set is solved with no digit twice (so a full house has the digits 1 through 9 once each).
+    pub(crate) fn check_solved_set(&mut self, set: DigitSet) -> Option<bool> {
         let mut used = vec![false; self.cells_per_set];
         let mut all_solved = true;
         for location in self.iter_indices(set) {
             let cell = self.get_mut(location);
-            cell.check_newly_solved();
+            if cell.check_newly_solved() {
+                cell.provenance = Provenance::Technique(Technique::NakedSingle);
+            }
             if let Some(solved) = cell.get_single_index() {
                 if used[solved] {
                     return Some(false);
@@ -651,95 +838,225 @@ impl Board {
         Some(all_solved)
     }
 
+    /// Returns true iff every house is solved without repeats and no constraint is broken.
     pub fn is_solved(&mut self) -> bool {
-        self.for_sets(Board::check_solved_set, BooleanOperation::AndLazy).unwrap()
+        self.for_sets(Board::check_solved_set, BooleanOperation::AndLazy).unwrap_or(false)
+            && !self.constraints.iter().any(|constraint| constraint.has_contradiction(self))
+    }
+
+    pub fn rows_per_box(&self) -> usize {
+        self.rows_per_box
+    }
+
+    pub fn cols_per_box(&self) -> usize {
+        self.cols_per_box
+    }
+
+    /// Number of cells in each row, col, and box (and the number of digits).
+    pub fn cells_per_set(&self) -> usize {
+        self.cells_per_set
+    }
+
+    /// The symbols used to write this board's digits.
+    pub fn symbols(&self) -> &SymbolSet {
+        &self.symbols
+    }
+
+    /// Changes the symbols used to write this board's digits, or returns an error if the set is the wrong size.
+    pub fn set_symbols(&mut self, symbols: SymbolSet) -> Result<(), SudokuError> {
+        if symbols.size() != self.cells_per_set {
+            return Err(SudokuError::WrongSymbolCount { expected: Some(self.cells_per_set), found: symbols.size() });
+        }
+        self.symbols = Arc::new(symbols);
+        Ok(())
+    }
+
+    /// Returns the board's symbol for the cell's digit, or `None` if the cell isn't down to one digit.
+    pub fn symbol_at(&self, location: Location) -> Option<char> {
+        self.try_get(location).ok()?.get_single_index().and_then(|digit| self.symbols.char_of(digit))
+    }
+
+    /// Writes the board on one line, one symbol per cell, with `.` for cells that aren't down to one digit.
+    /// This is the format `parse` reads.
+    pub fn to_line(&self) -> String {
+        self.tiles
+            .iter()
+            .map(|cell| cell.get_single_index().and_then(|digit| self.symbols.char_of(digit)).unwrap_or('.'))
+            .collect()
     }
 
     /// Returns a `DigitIterator` over the given set (which returns a type of `&Cell`).
-    pub fn iter_digits(&self, set: DigitSet) -> DigitIterator {
-        DigitIterator::new(self, set, self.cells_per_set)
+    pub fn iter_digits(&self, set: DigitSet) -> DigitIterator<'_> {
+        DigitIterator::new(self, set, Arc::clone(&self.geometry))
     }
 
-    /// Returns an `IndexIterator` over the given set (which returns a type of `(usize, usize)`).
+    /// Returns an `IndexIterator` over the given set (which returns a type of `Location`).
     pub fn iter_indices(&self, set: DigitSet) -> IndexIterator {
-        IndexIterator::new(set, self.cells_per_set)
+        IndexIterator::new(set, Arc::clone(&self.geometry))
     }
 }
 
-//Print the Board in a nice, readable format.
+/// Returns the most square `(rows_per_box, cols_per_box)` for a board with the given number of cells per set,
+/// with boxes wider than they are tall, e.g. 3 x 3 for 9, 3 x 4 for 12, and 4 x 4 for 16.
+pub fn box_shape(cells_per_set: usize) -> (usize, usize) {
+    let mut rows_per_box = 1;
+    for rows in 1..=cells_per_set {
+        if rows * rows > cells_per_set {
+            break;
+        }
+        if cells_per_set.is_multiple_of(rows) {
+            rows_per_box = rows;
+        }
+    }
+    (rows_per_box, cells_per_set / rows_per_box.max(1))
+}
+
+//Print the Board in a nice, readable format, with each cell's candidates drawn in it for `{:#}`.
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let max_digit_size = f32::log10(self.cells_per_set as f32).ceil() as usize;
-        let mut digit_spacing = "  ".to_string();
-        let mut thin_digit_spacing = "──".to_string();
-        let mut thick_digit_spacing = "━━".to_string();
-        for _ in 0..max_digit_size {
-            digit_spacing += " ";
-            thin_digit_spacing += "─";
-            thick_digit_spacing += "━";
-        }
-        let mut top_row = "".to_string();
-        let mut bottom_row = "".to_string();
-        let mut thin_row_divider = "".to_string();
-        let mut thick_row_divider = "".to_string();
-        for grouping in 0..self.rows_per_box {
-            for col in 0..self.cols_per_box {
-                top_row += &digit_spacing;
-                bottom_row += &digit_spacing;
-                thin_row_divider += &thin_digit_spacing;
-                thick_row_divider += &thick_digit_spacing;
-                if col < self.cols_per_box-1 {
-                    top_row += "╷";
-                    bottom_row += "╵";
-                    thin_row_divider += "┼";
-                    thick_row_divider += "┿";
-                }
-                else if grouping < self.rows_per_box-1 {
-                    top_row += "╻";
-                    bottom_row += "╹";
-                    thin_row_divider += "╂";
-                    thick_row_divider += "╋";
-                }
-            }
+        write!(f, "{}", self.render(&RenderOptions::new().pencil_marks(f.alternate())))
+    }
+}
+
+//Shows the shape, the board as `to_line` writes it, and the names of its constraints, since every cell's candidates would be too long to read.
+impl Debug for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let constraints: Vec<&str> = self.constraints.iter().map(|constraint| constraint.name()).collect();
+        f.debug_struct("Board")
+            .field("rows_per_box", &self.rows_per_box)
+            .field("cols_per_box", &self.cols_per_box)
+            .field("cells", &self.to_line())
+            .field("constraints", &constraints)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kropki::{Dot, DotColor, KropkiDots};
+
+    /// Solves the puzzle written one row per string, and checks it has exactly one solution, which keeps its givens.
+    fn assert_unique_solution(rows: &[&str]) {
+        let chars: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+        let puzzle = Board::from_chars(&chars);
+        assert_eq!(puzzle.count_solutions(2), 1);
+
+        let mut solution = puzzle.clone();
+        solution.fast_solve();
+        assert!(solution.is_solved());
+        assert!(!solution.has_contradiction());
+        for (given, solved) in puzzle.to_line().chars().zip(solution.to_line().chars()) {
+            assert!(given == '.' || given == solved);
         }
-        top_row += "\n";
-        thin_row_divider += "\n";
-        thick_row_divider += "\n";
-
-        let mut output = top_row;
-        for r in 0..self.cells_per_set {
-            for c in 0..self.cells_per_set {
-                let is_box_divider_col = (c + 1) % self.cols_per_box == 0 && c != self.cells_per_set - 1;
-                let digit = self.get(Location::Valid(r, c)).get_single_index();
-                if digit.is_some() {
-                    let digit = digit.unwrap() + 1;
-                    let current_digit_size = f32::log10(digit as f32).floor() as usize + 1;
-                    for _ in 0..max_digit_size - current_digit_size + 1 {
-                        output += " ";
-                    }
+    }
 
-                    output += &format!("{} ", digit);
-                }
-                else {
-                    output += &digit_spacing;
-                }
-                if is_box_divider_col {
-                    output += "┃";
-                }
-                else if c < self.cells_per_set - 1 {
-                    output += "│";
-                }
-            }
-            output += "\n";
-            let is_box_divider_row = (r + 1) % self.rows_per_box == 0 && r != self.cells_per_set - 1;
-            if is_box_divider_row {
-                output += &thick_row_divider;
-            }
-            else if r < self.cells_per_set - 1 {
-                output += &thin_row_divider;
-            }
+    #[test]
+    fn conflicting_cells_include_broken_constraints() {
+        let mut board = Board::new(3, 3);
+        board.add_constraint(KropkiDots::new(vec![Dot::new(DotColor::White, Location::Valid(0, 0), Location::Valid(0, 1))]));
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(9, 0));
+        board.set(Location::Valid(0, 1), Cell::new_single_digit(9, 4));
+        board.set(Location::Valid(8, 8), Cell::new_single_digit(9, 4));
+        board.set(Location::Valid(8, 0), Cell::new_single_digit(9, 4));
+        assert_eq!(board.conflicting_cells(), vec![Location::Valid(0, 0), Location::Valid(0, 1), Location::Valid(8, 0), Location::Valid(8, 8)]);
+    }
+
+    #[test]
+    fn debug_shows_the_shape_cells_and_constraints() {
+        let board = Board::parse("1.34\n34.2\n2.43\n43.1").unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!(format!("{board:?}"), r#"Board { rows_per_box: 2, cols_per_box: 2, cells: "1.3434.22.4343.1", constraints: ["rows", "cols", "boxes"] }"#);
+        assert_eq!(Board::try_new(0, 2).unwrap_err(), SudokuError::UnsupportedShape { rows_per_box: 0, cols_per_box: 2 });
+    }
+
+    #[test]
+    fn solves_9x9() {
+        //From very hard to easy; the first took far longer than the others for earlier versions of the solver.
+        for line in [
+            "....8..73.14...............7...3.....5....4.....6...2...64.52.....1.....3........",
+            "..........9..1..3...6.2.7.....3.4...21.....98...........25.64...8.....1..........",
+            "......9.1.5.7.....28..........5...6.1..4.....3.........6.....5.....32.......1.7..",
+            "...48.6........2...73.6......4..1.5...9....1..3.9..8.7.6..1.53....65.1..9..83....",
+            ".7....9..35..6...82.......14.9.2.8..6....8........31.....1..7..........6.8....534",
+            "9..37......31...4...7............317.8....9...92...4......9..5.56...2........4..1",
+            ".7.583.2..592..3..34...65.7795...632..36971..68...27..914835.76.3.7.1495567429.13",
+        ] {
+            let rows: Vec<&str> = (0..9).map(|r| &line[r * 9..(r + 1) * 9]).collect();
+            assert_unique_solution(&rows);
+        }
+    }
+
+    #[test]
+    fn finds_no_solution_to_an_impossible_puzzle() {
+        //Impossible, but seemed possible to an earlier version of the solver.
+        let mut board = Board::parse("...1.......................1............2...........1......1.....................").unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!(board.count_solutions(1), 0);
+        board.fast_solve();
+        assert!(!board.is_solved());
+    }
+
+    #[test]
+    fn fills_in_puzzles_with_many_solutions() {
+        for line in ["123456789".to_string() + &".".repeat(72), ".".repeat(81)] {
+            let puzzle = Board::parse(&line).unwrap_or_else(|error| panic!("{error}"));
+            assert_eq!(puzzle.count_solutions(2), 2);
+            let mut solution = puzzle.clone();
+            solution.fast_solve();
+            assert!(solution.is_solved() && !solution.has_contradiction());
+            assert!(line.chars().zip(solution.to_line().chars()).all(|(given, solved)| given == '.' || given == solved));
         }
-        output += &bottom_row;
-        writeln!(f, "{}", output)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn solves_16x16() {
+        assert_unique_solution(&[
+            "..B8...FD4.7C..1",
+            "F...1....83..4.G",
+            "....GD.7..6..8..",
+            ".....B.....C.5A.",
+            ".A5.E...8....7..",
+            ".B..........G.2.",
+            "3.47..962....F..",
+            ".E..D473.F.1.9.B",
+            "..6...E...7.....",
+            ".....G....9.8...",
+            "4..D..B81..25..9",
+            ".73B.6...DC42...",
+            "...3...AC..D....",
+            "D..G....F15..69.",
+            ".5.12.G....AB.7.",
+            ".8...F.E.3.B..C2",
+        ]);
+    }
+
+    #[test]
+    fn solves_25x25() {
+        assert_unique_solution(&[
+            "..N.7...E.1GJ...O..65...4",
+            "..P.L8.5.3.H.I.KJ....2.A.",
+            "3M....J.KG...D......FI9.E",
+            ".9E..D.L...A..N4M8..1..GK",
+            "...C.2.7.A.3.84...F..D...",
+            "F.9.E.D.OLN.2.B..6.5.3C..",
+            ".....A.E9FK1...O.H.L4...M",
+            "LD.H.6..M5..I.9..3.1N.2..",
+            "1.J.KG..........IAE.P.D..",
+            ".8.64.....PL....2GN7...F.",
+            "......L8.M.97..35...2K.B.",
+            ".1GK2N.I...J5.3...DO.P.M.",
+            "M..P.4.....O..H.1..B.N7.A",
+            "J5.4..1..B8...6A.N..DE..H",
+            "....I.FDH....K....8....J3",
+            "EA....H...B.G..8.........",
+            "P.DFOL6........C..JK..GN.",
+            ".3C5...B2..46....7..O.H..",
+            "N.....A.IEJ.....H.O..L6..",
+            "4.8.M5.....P.FD..1....AEI",
+            ".N7.A..H...2..1LP.....4..",
+            ".K1JG.N..I.....FE..D6O...",
+            "8...6.43.......1.J.......",
+            "..F.HOP6..AI.B..4M3CGJ...",
+            "C.5M..KG1.6.P.L.NBAI.9.D.",
+        ]);
+    }
+}
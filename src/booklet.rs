@@ -0,0 +1,238 @@
+use std::{fmt::Display, fmt::Write, str::FromStr};
+
+use crate::{board::Board, rating::Difficulty, svg::SvgOptions};
+
+/// How many solutions fit on each answer page, as (cols, rows).
+const SOLUTIONS_PER_PAGE: (usize, usize) = (2, 3);
+
+/// The paper a `Booklet` is laid out for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PaperSize {
+    /// 210 x 297 mm.
+    #[default]
+    A4,
+    /// 8.5 x 11 inches.
+    Letter,
+}
+
+impl PaperSize {
+    pub const ALL: [PaperSize; 2] = [PaperSize::A4, PaperSize::Letter];
+
+    /// Lowercase name, as accepted by `from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            PaperSize::A4 => "a4",
+            PaperSize::Letter => "letter",
+        }
+    }
+}
+
+impl Display for PaperSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for PaperSize {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|paper_size| paper_size.name().eq_ignore_ascii_case(input))
+            .ok_or_else(|| format!("unknown paper size '{input}' (expected a4 or letter)"))
+    }
+}
+
+/// One puzzle of a `Booklet`, with what's printed alongside it.
+#[derive(Clone, Debug)]
+pub struct BookletPuzzle {
+    pub puzzle: Board,
+    /// The solved puzzle, or the puzzle as far as the solver got if it has no solution.
+    pub solution: Board,
+    /// `None` if the puzzle has no solution.
+    pub difficulty: Option<Difficulty>,
+    /// The seed the puzzle was generated with, if it was.
+    pub seed: Option<u64>,
+}
+
+/// A printable collection of puzzles, one per page, with the solutions on the last pages.
+#[derive(Clone, Debug)]
+pub struct Booklet {
+    title: String,
+    paper_size: PaperSize,
+    puzzles: Vec<BookletPuzzle>,
+}
+
+impl Booklet {
+    /// Creates an empty booklet with the given title, which is printed at the top of every page.
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            paper_size: PaperSize::default(),
+            puzzles: Vec::new(),
+        }
+    }
+
+    pub fn paper_size(mut self, paper_size: PaperSize) -> Self {
+        self.paper_size = paper_size;
+        self
+    }
+
+    /// Adds a puzzle to the end of the booklet, solving and rating it for the page and the answers.
+    pub fn add_puzzle(&mut self, puzzle: Board, seed: Option<u64>) {
+        let mut solution = puzzle.clone();
+        solution.fast_solve();
+        let difficulty = puzzle.rate().map(|rating| rating.difficulty);
+        self.puzzles.push(BookletPuzzle { puzzle, solution, difficulty, seed });
+    }
+
+    pub fn puzzles(&self) -> &[BookletPuzzle] {
+        &self.puzzles
+    }
+
+    /// Writes the booklet as a self-contained HTML file, with the boards drawn as inline SVG,
+    /// and CSS that puts each puzzle on its own printed page.
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title);
+        let page_size = match self.paper_size {
+            PaperSize::A4 => "A4",
+            PaperSize::Letter => "letter",
+        };
+        let (cols, rows) = SOLUTIONS_PER_PAGE;
+
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+@page {{ size: {page_size}; margin: 15mm; }}
+body {{ margin: 0; font-family: sans-serif; }}
+.page {{ break-after: page; page-break-after: always; }}
+.page:last-child {{ break-after: auto; page-break-after: auto; }}
+header, footer {{ display: flex; justify-content: space-between; font-size: 12pt; }}
+header {{ border-bottom: 1px solid black; padding-bottom: 2mm; margin-bottom: 10mm; }}
+footer {{ margin-top: 6mm; }}
+.board svg {{ display: block; width: 100%; height: auto; }}
+.puzzle .board {{ margin: 0 auto; max-width: 170mm; }}
+.solutions {{ display: grid; grid-template-columns: repeat({cols}, 1fr); gap: 8mm; }}
+.solutions figure {{ margin: 0; }}
+.solutions figcaption {{ text-align: center; margin-bottom: 2mm; }}
+</style>
+</head>
+<body>
+"
+        );
+
+        for (index, entry) in self.puzzles.iter().enumerate() {
+            let number = index + 1;
+            let difficulty = entry.difficulty.map_or("unsolvable".to_string(), |difficulty| difficulty.to_string());
+            let seed = entry.seed.map_or(String::new(), |seed| format!("<span>Seed {seed}</span>"));
+            let _ = write!(
+                html,
+                "<section class=\"page puzzle\">
+<header><span>{title}</span><span>Puzzle {number}</span></header>
+<div class=\"board\">
+{}</div>
+<footer><span>Difficulty: {difficulty}</span>{seed}</footer>
+</section>
+",
+                entry.puzzle.to_svg(&SvgOptions::new())
+            );
+        }
+
+        for (page, entries) in self.puzzles.chunks(cols * rows).enumerate() {
+            let _ = write!(
+                html,
+                "<section class=\"page\">
+<header><span>{title}</span><span>Solutions{}</span></header>
+<div class=\"solutions\">
+",
+                if self.puzzles.len() > cols * rows { format!(" ({})", page + 1) } else { String::new() }
+            );
+            for (offset, entry) in entries.iter().enumerate() {
+                let number = page * cols * rows + offset + 1;
+                let _ = write!(
+                    html,
+                    "<figure class=\"board\"><figcaption>Puzzle {number}</figcaption>
+{}</figure>
+",
+                    entry.solution.to_svg(&SvgOptions::new())
+                );
+            }
+            html += "</div>\n</section>\n";
+        }
+
+        html += "</body>\n</html>\n";
+        html
+    }
+}
+
+fn escape_html(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle(rows: [&str; 4]) -> Board {
+        let rows: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+        Board::from_chars(&rows)
+    }
+
+    #[test]
+    fn paper_size_names_round_trip() {
+        for paper_size in PaperSize::ALL {
+            assert_eq!(paper_size.to_string().parse::<PaperSize>(), Ok(paper_size));
+        }
+        assert_eq!("LETTER".parse::<PaperSize>(), Ok(PaperSize::Letter));
+        assert!("a5".parse::<PaperSize>().is_err());
+    }
+
+    #[test]
+    fn add_puzzle_solves_and_rates() {
+        let mut booklet = Booklet::new("Test");
+        booklet.add_puzzle(puzzle(["1.34", "34.2", "2.43", "43.1"]), Some(7));
+        booklet.add_puzzle(puzzle(["11..", "....", "....", "...."]), None);
+
+        let [solvable, unsolvable] = booklet.puzzles() else { panic!("expected two puzzles") };
+        assert!(solvable.solution.clone().is_solved());
+        assert_eq!(solvable.difficulty, Some(Difficulty::Easy));
+        assert_eq!(solvable.seed, Some(7));
+        assert_eq!(unsolvable.difficulty, None);
+    }
+
+    #[test]
+    fn html_has_a_page_per_puzzle_and_pages_of_solutions() {
+        let mut booklet = Booklet::new("Tom & Jerry's <puzzles>").paper_size(PaperSize::Letter);
+        for seed in 0..7 {
+            booklet.add_puzzle(puzzle(["1.34", "34.2", "2.43", "43.1"]), Some(seed));
+        }
+        let html = booklet.to_html();
+
+        assert!(html.contains("<title>Tom &amp; Jerry's &lt;puzzles&gt;</title>"));
+        assert!(html.contains("size: letter;"));
+        assert_eq!(html.matches("<section class=\"page puzzle\">").count(), 7);
+        assert!(html.contains("<span>Puzzle 7</span>"));
+        assert!(html.contains("<span>Seed 6</span>"));
+        assert!(html.contains("Difficulty: easy"));
+        assert!(html.contains("Solutions (1)") && html.contains("Solutions (2)"));
+        assert_eq!(html.matches("<figure class=\"board\">").count(), 7);
+        assert_eq!(html.matches("<svg").count(), 14);
+    }
+}
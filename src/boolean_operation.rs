@@ -1,6 +1,6 @@
 /// Enum to specify the operation type when combining multiple operations (for conciseness).
 #[derive(Clone, Copy)]
-pub enum BooleanOperation {
+pub(crate) enum BooleanOperation {
     Or,
     OrLazy,
     And,
@@ -9,7 +9,7 @@ pub enum BooleanOperation {
 
 impl BooleanOperation {
     /// Initial value for the operation type.
-    pub fn initial(self) -> bool {
+    pub(crate) fn initial(self) -> bool {
         match self {
             BooleanOperation::Or | BooleanOperation::OrLazy => false,
             BooleanOperation::And | BooleanOperation::AndLazy => true,
@@ -17,7 +17,7 @@ impl BooleanOperation {
     }
 
     /// Combines the current result with the new value based on the operation type.
-    pub fn combine(self, current: bool, next: bool) -> bool {
+    pub(crate) fn combine(self, current: bool, next: bool) -> bool {
         match self {
             BooleanOperation::Or => current | next,
             BooleanOperation::OrLazy => current || next,
@@ -1,10 +1,34 @@
 use std::fmt::Display;
 
+use crate::{error::SudokuError, provenance::Provenance};
+
+/// The characters used for each digit, in order, so boards up to 64x64 can be written one char per cell.
+pub const DIGIT_CHARS: &str = "123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz@#$";
+
+/// Converts a char to a digit index using `DIGIT_CHARS`, or returns `None` if it isn't a digit of a board with the given size.
+/// Boards of size 35 or less don't use lowercase letters, so lowercase input is read as uppercase for those.
+pub fn digit_from_char(size: usize, input: char) -> Option<usize> {
+    let input = if size <= 35 { input.to_ascii_uppercase() } else { input };
+    DIGIT_CHARS
+        .chars()
+        .take(size)
+        .position(|digit_char| digit_char == input)
+}
+
+/// Converts a digit index to its char in `DIGIT_CHARS`.
+pub fn char_from_digit(digit: usize) -> Option<char> {
+    DIGIT_CHARS.chars().nth(digit)
+}
+
 /// Represents a cell in the Sudoku, storing a list of the digits the cell could be.
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cell {
     pub possibilities: Vec<bool>,
     pub solved: bool,
+    /// How the cell came to be solved.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub provenance: Provenance,
 }
 
 impl Cell {
@@ -13,10 +37,20 @@ impl Cell {
         Self {
             possibilities: vec![true; size],
             solved: false,
+            provenance: Provenance::Unknown,
         }
     }
 
+    /// Creates a solved Cell with the given digit, or an error if the digit is too large.
+    pub fn try_new_single_digit(size: usize, input: usize) -> Result<Self, SudokuError> {
+        if input >= size {
+            return Err(SudokuError::InvalidDigit { size, digit: input });
+        }
+        Ok(Self::new_single_digit(size, input))
+    }
+
     /// Creates a solved Cell with the given digit.
+    /// Panics if the digit is too large; see `try_new_single_digit`.
     pub fn new_single_digit(size: usize, input: usize) -> Self {
         let mut possibilities = vec![false; size];
         possibilities[input] = true;
@@ -24,22 +58,33 @@ impl Cell {
         Self {
             possibilities,
             solved: true,
+            provenance: Provenance::Unknown,
         }
     }
 
+    /// Returns the cell with its provenance changed to the given one.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// Returns true iff the cell is a clue of the puzzle.
+    pub fn is_given(&self) -> bool {
+        self.provenance == Provenance::Given
+    }
+
+    /// Creates a solved Cell with the given char converted to a digit, or an error if the char isn't a digit.
+    pub fn try_new_single_char(size: usize, input: char) -> Result<Self, SudokuError> {
+        let digit = digit_from_char(size, input).ok_or(SudokuError::InvalidChar { size, input })?;
+        Ok(Self::new_single_digit(size, digit))
+    }
+
     /// Creates a solved Cell with the given char converted to a digit.
+    /// Panics on invalid input; see `try_new_single_char`.
     pub fn new_single_char(size: usize, input: char) -> Self {
-        let mut possibilities = vec![false; size];
-        if let Some(digit) = input.to_digit(10) {
-            possibilities[digit as usize - 1] = true;
-        }
-        else {
-            panic!("Invalid Cell input: {input}");
-        }
-
-        Self {
-            possibilities,
-            solved: true,
+        match Self::try_new_single_char(size, input) {
+            Ok(cell) => cell,
+            Err(error) => panic!("Invalid Cell input: {error}"),
         }
     }
 
@@ -89,10 +134,12 @@ impl Cell {
     }
 }
 
+/// A debugging format: the cell's digit in `DIGIT_CHARS`, or a space if it isn't down to one digit.
+/// It doesn't know the board's symbols, so anything shown to players goes through `Board::symbol_at` or `Board::render` instead.
 impl Display for Cell {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(digit_index) = self.get_single_index() {
-            write!(f, "{}", digit_index+1)
+        if let Some(digit) = self.get_single_index().and_then(char_from_digit) {
+            write!(f, "{digit}")
         }
         else {
             write!(f, " ")
@@ -0,0 +1,907 @@
+use std::{
+    fs,
+    io::{self, Read, Write},
+    process::ExitCode,
+    time::Instant
+};
+
+use sudoku_solver::{
+    box_shape, cell_name, Arrows, Board, Booklet, Difficulty, JigsawRegions, KillerCages, KillerGenerator, KropkiDots, KropkiGenerator, Location, PaperSize, PuzzleGenerator, Rating,
+    RenderOptions, SymbolSet, Symmetry, Thermometers, Variant
+};
+
+/// Every puzzle was handled successfully.
+pub const EXIT_SUCCESS: u8 = 0;
+/// At least one puzzle was unsolvable, invalid, or didn't have a unique solution.
+pub const EXIT_PUZZLE_FAILED: u8 = 1;
+/// The command line couldn't be understood.
+pub const EXIT_USAGE: u8 = 2;
+/// The input couldn't be read or parsed, or the output couldn't be written.
+pub const EXIT_INPUT: u8 = 3;
+
+/// Printed after a usage error, instead of the whole of `USAGE`.
+const USAGE_HINT: &str = "Run 'sudoku_solver --help' for usage.";
+
+const USAGE: &str = "\
+Usage: sudoku_solver <COMMAND> [OPTIONS]
+
+Commands:
+  solve [PUZZLE]...     Solve each puzzle
+  generate              Generate new puzzles with exactly one solution
+  rate [PUZZLE]...      Rate how hard each puzzle is (easy, medium, hard, or expert)
+  validate [PUZZLE]...  Check each puzzle's givens for conflicts and a unique solution
+  bench                 Time the solver on generated puzzles
+  booklet [PUZZLE]...   Write a printable HTML booklet of puzzles, one per page, with the solutions at the end
+  help                  Print this message
+
+Puzzles are written one per line, one char per cell, with '.' or '0' for blanks,
+or drawn over several lines as a grid like the one solve prints (or in ASCII with |, -, and +).
+solve, rate, and validate read the puzzles given as arguments and in --file,
+or standard input if there are none. Blank lines and lines starting with '#' are skipped.
+booklet reads puzzles the same way, but generates them (like generate) if none are given.
+
+Options:
+  -f, --file <PATH>          Read puzzles from a file (can be repeated)
+      --format <FORMAT>      human (default) or json, which prints one JSON object per line
+  -s, --size <SIZE>          Box shape as ROWSxCOLS (e.g. 3x4), or cells per row (e.g. 9)
+                             [default: 3x3 for generate, or inferred from the number of cells]
+  -n, --count <N>            Number of puzzles to generate [default: 1, or 1000 for bench]
+      --seed <SEED>          Seed for the first puzzle; puzzle i uses SEED + i [default: random]
+      --symmetry <NAME>      none, rotational, quarter, horizontal, vertical, or diagonal [default: none]
+      --difficulty <LEVEL>   easy, medium, hard, or expert [default: as hard as possible]
+      --variant <NAME>       Extra rule every puzzle follows: diagonal (Sudoku-X), hyper (Windoku),
+                             anti-knight, or anti-king; can be repeated
+      --regions <PATH>       Make every puzzle a jigsaw puzzle, with the regions in this file in place of
+                             the boxes: a line per row, with a letter per cell naming its region
+      --cages <PATH>         Make every puzzle a killer puzzle, with the cages in this file: a line per row,
+                             with a letter per cell naming its cage (or '.' for none), then the cage sums
+                             in order; with no puzzles given, solves the cages alone
+      --thermos <PATH>       Add the thermometers in this file to every puzzle: a line per thermometer,
+                             with its cells from the bulb up, e.g. r1c1 r1c2 r2c3
+      --arrows <PATH>        Add the arrows in this file to every puzzle: a line per arrow, with the cells
+                             of its circle, '=', then the cells along it, e.g. r1c1 = r1c2 r2c3
+      --dots <PATH>          Add the Kropki dots in this file to every puzzle: a line per dot, with its color
+                             (white or black) and two cells, e.g. white r1c1 r1c2, and a line saying
+                             'all dots given' if neighbors with no dot can't be consecutive or double
+      --killer               Generate killer puzzles, printing the cages after each puzzle
+      --cage-size <MIN-MAX>  Cells per cage in generated killer puzzles, e.g. 2-4 [default: 2-5]
+      --givens <N>           Givens in generated killer puzzles [default: 0]
+      --kropki               Generate Kropki puzzles, printing the dots after each puzzle
+      --all-dots             Show every dot in generated Kropki puzzles, so missing dots are clues too
+      --symbols <SYMBOLS>    digits (1-9A-Z...), hex (0-9A-F), letters (A-Z), or the symbols themselves
+                             in order, e.g. WORDPLAYS for Wordoku [default: digits]
+      --paper <SIZE>         Paper the booklet is laid out for: a4 or letter [default: a4]
+      --title <TITLE>        Title printed at the top of each booklet page [default: Sudoku]
+  -o, --output <PATH>        Write the booklet to a file instead of standard output
+  -h, --help                 Print this message
+
+Boards are colored when printed to a terminal, unless the NO_COLOR environment variable is set.
+
+Exit codes:
+  0  Success
+  1  At least one puzzle was unsolvable, invalid, or not unique
+  2  The command line couldn't be understood
+  3  The input couldn't be read or parsed, or the output couldn't be written";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Command {
+    Solve,
+    Generate,
+    Rate,
+    Validate,
+    Bench,
+    Booklet,
+    Help,
+}
+
+enum Symbols {
+    Digits,
+    Hex,
+    Letters,
+    Custom(SymbolSet),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Human,
+    Json,
+}
+
+struct Options {
+    command: Command,
+    format: Format,
+    puzzles: Vec<String>,
+    files: Vec<String>,
+    shape: Option<(usize, usize)>,
+    count: Option<usize>,
+    seed: Option<u64>,
+    symmetry: Symmetry,
+    difficulty: Option<Difficulty>,
+    symbols: Symbols,
+    variants: Vec<Variant>,
+    regions: Option<JigsawRegions>,
+    //The cages, and the number of cells per row of the map they were read from.
+    cages: Option<(KillerCages, usize)>,
+    thermometers: Option<Thermometers>,
+    arrows: Option<Arrows>,
+    dots: Option<KropkiDots>,
+    killer: bool,
+    cage_sizes: Option<(usize, usize)>,
+    givens: Option<usize>,
+    kropki: bool,
+    all_dots: bool,
+    paper_size: PaperSize,
+    title: Option<String>,
+    output: Option<String>,
+}
+
+enum CliError {
+    Usage(String),
+    Input(String),
+    Output(io::Error),
+}
+
+impl CliError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::Input(_) | CliError::Output(_) => EXIT_INPUT,
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        CliError::Output(error)
+    }
+}
+
+/// Runs the command line and returns the exit code to finish with.
+pub fn run(args: &[String]) -> ExitCode {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let result = parse_args(args).and_then(|options| run_command(&options, &mut out)).and_then(|code| {
+        out.flush()?;
+        Ok(code)
+    });
+
+    match result {
+        Ok(code) => ExitCode::from(code),
+        //Whatever was reading the output (e.g. `head`) has all it wants, so stop without complaint.
+        Err(CliError::Output(error)) if error.kind() == io::ErrorKind::BrokenPipe => ExitCode::from(EXIT_SUCCESS),
+        Err(error) => {
+            match &error {
+                CliError::Usage(message) => eprintln!("error: {message}\n{USAGE_HINT}"),
+                CliError::Input(message) => eprintln!("error: {message}"),
+                CliError::Output(error) => eprintln!("error: couldn't write the output: {error}"),
+            }
+            ExitCode::from(error.exit_code())
+        }
+    }
+}
+
+/// Runs the command, writing what it prints to `out`.
+fn run_command(options: &Options, out: &mut impl Write) -> Result<u8, CliError> {
+    match options.command {
+        Command::Solve => solve(options, out),
+        Command::Generate => generate(options, out),
+        Command::Rate => rate(options, out),
+        Command::Validate => validate(options, out),
+        Command::Bench => bench(options, out),
+        Command::Booklet => booklet(options, out),
+        Command::Help => {
+            writeln!(out, "{USAGE}")?;
+            Ok(EXIT_SUCCESS)
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, CliError> {
+    let mut args = args.iter();
+    let command = match args.next().map(String::as_str) {
+        Some("solve") => Command::Solve,
+        Some("generate") => Command::Generate,
+        Some("rate") => Command::Rate,
+        Some("validate") => Command::Validate,
+        Some("bench") => Command::Bench,
+        Some("booklet") => Command::Booklet,
+        Some("help" | "-h" | "--help") => Command::Help,
+        Some(other) => return Err(CliError::Usage(format!("unknown command '{other}'"))),
+        None => return Err(CliError::Usage("no command given".to_string())),
+    };
+
+    let mut options = Options {
+        command,
+        format: Format::Human,
+        puzzles: Vec::new(),
+        files: Vec::new(),
+        shape: None,
+        count: None,
+        seed: None,
+        symmetry: Symmetry::None,
+        difficulty: None,
+        symbols: Symbols::Digits,
+        variants: Vec::new(),
+        regions: None,
+        cages: None,
+        thermometers: None,
+        arrows: None,
+        dots: None,
+        killer: false,
+        cage_sizes: None,
+        givens: None,
+        kropki: false,
+        all_dots: false,
+        paper_size: PaperSize::default(),
+        title: None,
+        output: None,
+    };
+
+    let reads_puzzles = matches!(command, Command::Solve | Command::Rate | Command::Validate | Command::Booklet);
+    let makes_puzzles = matches!(command, Command::Generate | Command::Bench | Command::Booklet);
+    let generates = matches!(command, Command::Generate | Command::Booklet);
+    while let Some(arg) = args.next() {
+        let arg = arg.as_str();
+        if !arg.starts_with('-') {
+            if !reads_puzzles {
+                return Err(CliError::Usage(format!("unexpected argument '{arg}'")));
+            }
+            options.puzzles.push(arg.to_string());
+            continue;
+        }
+
+        let flag = arg;
+        if flag == "-h" || flag == "--help" {
+            options.command = Command::Help;
+            return Ok(options);
+        }
+        let mut value = || args.next().cloned().ok_or_else(|| CliError::Usage(format!("{flag} needs a value")));
+        match flag {
+            "--format" if command != Command::Booklet => {
+                options.format = match value()?.as_str() {
+                    "human" => Format::Human,
+                    "json" => Format::Json,
+                    other => return Err(CliError::Usage(format!("unknown format '{other}' (expected human or json)"))),
+                };
+            }
+            "-f" | "--file" if reads_puzzles => options.files.push(value()?),
+            "-s" | "--size" => options.shape = Some(parse_shape(&value()?)?),
+            "-n" | "--count" if makes_puzzles => options.count = Some(parse_number(flag, &value()?)?),
+            "--seed" if generates => options.seed = Some(parse_number(flag, &value()?)?),
+            "--symmetry" if generates => options.symmetry = value()?.parse().map_err(CliError::Usage)?,
+            "--difficulty" if generates => options.difficulty = Some(value()?.parse().map_err(CliError::Usage)?),
+            "--variant" => options.variants.push(value()?.parse().map_err(CliError::Usage)?),
+            "--regions" => options.regions = Some(read_regions(&value()?)?),
+            "--cages" if reads_puzzles => options.cages = Some(read_cages(&value()?)?),
+            "--thermos" if reads_puzzles => options.thermometers = Some(read_thermometers(&value()?)?),
+            "--arrows" if reads_puzzles => options.arrows = Some(read_arrows(&value()?)?),
+            "--dots" if reads_puzzles => options.dots = Some(read_dots(&value()?)?),
+            "--killer" if command == Command::Generate => options.killer = true,
+            "--cage-size" if command == Command::Generate => options.cage_sizes = Some(parse_range(flag, &value()?)?),
+            "--givens" if command == Command::Generate => options.givens = Some(parse_number(flag, &value()?)?),
+            "--kropki" if command == Command::Generate => options.kropki = true,
+            "--all-dots" if command == Command::Generate => options.all_dots = true,
+            "--paper" if command == Command::Booklet => options.paper_size = value()?.parse().map_err(CliError::Usage)?,
+            "--title" if command == Command::Booklet => options.title = Some(value()?),
+            "-o" | "--output" if command == Command::Booklet => options.output = Some(value()?),
+            "--symbols" if command != Command::Bench => {
+                options.symbols = match value()?.as_str() {
+                    "digits" => Symbols::Digits,
+                    "hex" => Symbols::Hex,
+                    "letters" => Symbols::Letters,
+                    custom => Symbols::Custom(SymbolSet::new(custom).map_err(|error| CliError::Usage(error.to_string()))?),
+                };
+            }
+            _ => return Err(CliError::Usage(format!("unexpected option '{flag}'"))),
+        }
+    }
+
+    if !options.killer && (options.cage_sizes.is_some() || options.givens.is_some()) {
+        return Err(CliError::Usage("--cage-size and --givens need --killer".to_string()));
+    }
+    if options.killer && (!options.variants.is_empty() || options.regions.is_some() || options.symmetry != Symmetry::None) {
+        return Err(CliError::Usage("--killer can't be combined with --variant, --regions, or --symmetry".to_string()));
+    }
+    if !options.kropki && options.all_dots {
+        return Err(CliError::Usage("--all-dots needs --kropki".to_string()));
+    }
+    if options.kropki && (options.killer || !options.variants.is_empty() || options.regions.is_some() || options.symmetry != Symmetry::None || options.difficulty.is_some()) {
+        return Err(CliError::Usage("--kropki can't be combined with --killer, --variant, --regions, --symmetry, or --difficulty".to_string()));
+    }
+
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, input: &str) -> Result<T, CliError> {
+    input.parse().map_err(|_| CliError::Usage(format!("{flag} expects a number, not '{input}'")))
+}
+
+/// Reads a range written as `MIN-MAX`, or as a single number.
+fn parse_range(flag: &str, input: &str) -> Result<(usize, usize), CliError> {
+    let (min, max) = match input.split_once('-') {
+        Some((min, max)) => (parse_number(flag, min)?, parse_number(flag, max)?),
+        None => {
+            let size = parse_number(flag, input)?;
+            (size, size)
+        }
+    };
+    if min == 0 || min > max {
+        return Err(CliError::Usage(format!("{flag} expects MIN-MAX with 1 <= MIN <= MAX, not '{input}'")));
+    }
+    Ok((min, max))
+}
+
+/// Reads a box shape written as `ROWSxCOLS`, or as the number of cells per row.
+fn parse_shape(input: &str) -> Result<(usize, usize), CliError> {
+    let shape = match input.split_once(['x', 'X']) {
+        Some((rows, cols)) => (parse_number("--size", rows)?, parse_number("--size", cols)?),
+        None => box_shape(parse_number("--size", input)?),
+    };
+    //Make sure the shape is one `Board` accepts.
+    Board::try_new(shape.0, shape.1).map_err(|error| CliError::Usage(error.to_string()))?;
+    Ok(shape)
+}
+
+/// Reads a jigsaw region map from a file.
+fn read_regions(path: &str) -> Result<JigsawRegions, CliError> {
+    let text = fs::read_to_string(path).map_err(|error| CliError::Input(format!("couldn't read {path}: {error}")))?;
+    JigsawRegions::parse_map(&text).map_err(|error| CliError::Input(format!("{path}: {error}")))
+}
+
+/// Reads killer cages from a file, along with the number of rows of their map.
+fn read_cages(path: &str) -> Result<(KillerCages, usize), CliError> {
+    let text = fs::read_to_string(path).map_err(|error| CliError::Input(format!("couldn't read {path}: {error}")))?;
+    let cages = KillerCages::parse(&text).map_err(|error| CliError::Input(format!("{path}: {error}")))?;
+    let size = text.lines().filter(|line| line.chars().any(|c| c.is_ascii_alphabetic() || c == '.')).count();
+    Ok((cages, size))
+}
+
+/// Reads thermometers from a file.
+fn read_thermometers(path: &str) -> Result<Thermometers, CliError> {
+    let text = fs::read_to_string(path).map_err(|error| CliError::Input(format!("couldn't read {path}: {error}")))?;
+    Thermometers::parse(&text).map_err(|error| CliError::Input(format!("{path}: {error}")))
+}
+
+/// Reads arrows from a file.
+fn read_arrows(path: &str) -> Result<Arrows, CliError> {
+    let text = fs::read_to_string(path).map_err(|error| CliError::Input(format!("couldn't read {path}: {error}")))?;
+    Arrows::parse(&text).map_err(|error| CliError::Input(format!("{path}: {error}")))
+}
+
+/// Reads Kropki dots from a file.
+fn read_dots(path: &str) -> Result<KropkiDots, CliError> {
+    let text = fs::read_to_string(path).map_err(|error| CliError::Input(format!("couldn't read {path}: {error}")))?;
+    KropkiDots::parse(&text).map_err(|error| CliError::Input(format!("{path}: {error}")))
+}
+
+/// The box shape from --size, or else the one for the size of the --regions map, or else the default.
+fn shape_or(options: &Options, default: (usize, usize)) -> (usize, usize) {
+    match (options.shape, &options.regions) {
+        (Some(shape), _) => shape,
+        (None, Some(regions)) => box_shape(regions.size()),
+        (None, None) => default,
+    }
+}
+
+/// Collects the puzzles from the arguments and files, or standard input if there are neither.
+fn read_puzzles(options: &Options) -> Result<Vec<String>, CliError> {
+    let mut puzzles = Vec::new();
+    for puzzle in options.puzzles.iter() {
+        split_puzzles(puzzle, &mut puzzles);
+    }
+    for path in options.files.iter() {
+        let text = fs::read_to_string(path).map_err(|error| CliError::Input(format!("couldn't read {path}: {error}")))?;
+        split_puzzles(&text, &mut puzzles);
+    }
+    if let (true, true, Some((_, size))) = (options.puzzles.is_empty(), options.files.is_empty(), &options.cages) {
+        //The cages are the whole puzzle.
+        puzzles.push(".".repeat(size * size));
+    }
+    else if options.puzzles.is_empty() && options.files.is_empty() {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map_err(|error| CliError::Input(format!("couldn't read standard input: {error}")))?;
+        split_puzzles(&text, &mut puzzles);
+    }
+    Ok(puzzles)
+}
+
+/// Splits text into puzzles, one per line, except that a run of lines drawing a grid is one puzzle.
+/// Grid lines are kept as they are, since their indentation shows which cells are blank.
+fn split_puzzles(text: &str, puzzles: &mut Vec<String>) {
+    let mut grid = String::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if !trimmed.starts_with('#') && trimmed.chars().any(is_grid_char) {
+            grid += line;
+            grid.push('\n');
+            //The bottom edge of a grid like the one `Board` displays, so grids printed back to back stay separate.
+            if trimmed.contains(['╵', '╹']) {
+                puzzles.push(std::mem::take(&mut grid));
+            }
+            continue;
+        }
+        if !grid.is_empty() {
+            puzzles.push(std::mem::take(&mut grid));
+        }
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            puzzles.push(trimmed.to_string());
+        }
+    }
+    if !grid.is_empty() {
+        puzzles.push(grid);
+    }
+}
+
+fn is_grid_char(input: char) -> bool {
+    matches!(input, '|' | '+' | '-' | '=') || ('\u{2500}'..='\u{257F}').contains(&input)
+}
+
+/// Returns the symbols to use for a board with the given number of cells per set, or `None` for the default digits.
+fn symbols_for(options: &Options, cells_per_set: usize) -> Result<Option<SymbolSet>, String> {
+    match &options.symbols {
+        Symbols::Digits => Ok(None),
+        Symbols::Hex => Ok(Some(SymbolSet::hex())),
+        Symbols::Letters => SymbolSet::letters(cells_per_set).map(Some).map_err(|error| error.to_string()),
+        Symbols::Custom(symbols) => Ok(Some(symbols.clone())),
+    }
+}
+
+/// Reads a puzzle, using the box shape from --size and the symbols from --symbols if there are any.
+fn parse_puzzle(options: &Options, number: usize, line: &str) -> Result<Board, CliError> {
+    let cells_per_set = match options.shape {
+        Some((rows_per_box, cols_per_box)) => rows_per_box * cols_per_box,
+        //A grid has a line per row, besides the lines between rows which only have grid chars.
+        None if line.chars().any(is_grid_char) => line
+            .lines()
+            .filter(|row| row.chars().any(|c| !c.is_whitespace() && !is_grid_char(c)) || row.contains(['|', '│', '┃', '║']) && !row.contains(['-', '=', '─', '━', '═']))
+            .count(),
+        None => line.chars().filter(|c| !c.is_whitespace()).count().isqrt(),
+    };
+    let board = match (symbols_for(options, cells_per_set), options.shape) {
+        (Err(error), _) => return Err(CliError::Input(format!("puzzle {number}: {error}"))),
+        (Ok(Some(symbols)), shape) => Board::parse_with_symbols(line, symbols, shape),
+        (Ok(None), Some((rows_per_box, cols_per_box))) => Board::parse_with_shape(line, rows_per_box, cols_per_box),
+        (Ok(None), None) => Board::parse(line),
+    };
+    let mut board = board.map_err(|error| CliError::Input(format!("puzzle {number}: {error}")))?;
+    for variant in options.variants.iter() {
+        board.try_add_shared_constraint(variant.constraint()).map_err(|error| CliError::Input(format!("puzzle {number}: {error}")))?;
+    }
+    if let Some(regions) = &options.regions {
+        board.try_set_regions(regions.clone()).map_err(|error| CliError::Input(format!("puzzle {number}: {error}")))?;
+    }
+    if let Some((cages, _)) = &options.cages {
+        board.try_add_constraint(cages.clone()).map_err(|error| CliError::Input(format!("puzzle {number}: {error}")))?;
+    }
+    if let Some(thermometers) = &options.thermometers {
+        board.try_add_constraint(thermometers.clone()).map_err(|error| CliError::Input(format!("puzzle {number}: {error}")))?;
+    }
+    if let Some(arrows) = &options.arrows {
+        board.try_add_constraint(arrows.clone()).map_err(|error| CliError::Input(format!("puzzle {number}: {error}")))?;
+    }
+    if let Some(dots) = &options.dots {
+        board.try_add_constraint(dots.clone()).map_err(|error| CliError::Input(format!("puzzle {number}: {error}")))?;
+    }
+    Ok(board)
+}
+
+fn solve(options: &Options, out: &mut impl Write) -> Result<u8, CliError> {
+    let mut code = EXIT_SUCCESS;
+    for (index, line) in read_puzzles(options)?.iter().enumerate() {
+        let number = index + 1;
+        let original = parse_puzzle(options, number, line)?;
+        let mut board = original.clone();
+        let stats = board.fast_solve_with_stats();
+        if !stats.solved {
+            code = EXIT_PUZZLE_FAILED;
+        }
+
+        match options.format {
+            Format::Human if stats.solved => {
+                let solution = board.render_solution(&original, &RenderOptions::new().auto_color());
+                writeln!(out, "Puzzle {number}: solved with {} guesses\n{solution}", stats.guesses)?;
+            }
+            Format::Human => writeln!(out, "Puzzle {number}: no solution\n{}", original.render(&RenderOptions::new().auto_color()))?,
+            Format::Json => {
+                let solution = if stats.solved { json_string(&board.to_line()) } else { "null".to_string() };
+                let fields = [
+                    ("puzzle", number.to_string()),
+                    ("input", json_string(line)),
+                    ("solved", stats.solved.to_string()),
+                    ("guesses", stats.guesses.to_string()),
+                    ("solution", solution),
+                ];
+                writeln!(out, "{}", json_object(&fields))?;
+            }
+        }
+    }
+    Ok(code)
+}
+
+fn generate(options: &Options, out: &mut impl Write) -> Result<u8, CliError> {
+    let (rows_per_box, cols_per_box) = shape_or(options, (3, 3));
+    let base_seed = options.seed.unwrap_or_else(rand::random);
+    let variants: Vec<String> = options.variants.iter().map(|variant| json_string(variant.name())).collect();
+    for index in 0..options.count.unwrap_or(1) {
+        let number = index + 1;
+        let seed = base_seed.wrapping_add(index as u64);
+        //The killer generator rates its puzzles as it goes, so they don't need rating again.
+        let (puzzle, rating) = if options.killer {
+            let (puzzle, rating) = generate_killer(options, seed)?;
+            (puzzle, Some(rating))
+        }
+        else {
+            let puzzle = if options.kropki {
+                generate_kropki(options, seed)?
+            }
+            else {
+                generate_puzzle(options, seed)?
+            };
+            let rating = puzzle.rate();
+            (puzzle, rating)
+        };
+        let cages = puzzle.constraint::<KillerCages>().map(|cages| cages.try_to_text(puzzle.cells_per_set())).transpose().map_err(|error| CliError::Usage(error.to_string()))?;
+        let dots = puzzle.constraint::<KropkiDots>().map(KropkiDots::to_text);
+        let difficulty = rating.map_or("unrated".to_string(), |rating| rating.difficulty.to_string());
+        let mut solution = puzzle.clone();
+        solution.fast_solve();
+
+        match options.format {
+            Format::Human => {
+                //The cages or dots go after the puzzle, in the form --cages or --dots reads.
+                let clues: String = cages.iter().chain(dots.iter()).map(String::as_str).collect();
+                writeln!(out, "Puzzle {number} (seed {seed}, {difficulty}):\n{puzzle}{}\n{clues}", puzzle.to_line())?;
+            }
+            Format::Json => {
+                let mut fields = vec![
+                    ("puzzle", number.to_string()),
+                    ("seed", seed.to_string()),
+                    ("rows_per_box", rows_per_box.to_string()),
+                    ("cols_per_box", cols_per_box.to_string()),
+                    ("variants", json_array(&variants)),
+                    ("symmetry", json_string(options.symmetry.name())),
+                    ("difficulty", json_string(&difficulty)),
+                    ("givens", json_string(&puzzle.to_line())),
+                ];
+                fields.extend(cages.iter().map(|cages| ("cages", json_string(cages))));
+                fields.extend(dots.iter().map(|dots| ("dots", json_string(dots))));
+                fields.push(("solution", json_string(&solution.to_line())));
+                writeln!(out, "{}", json_object(&fields))?;
+            }
+        }
+    }
+    Ok(EXIT_SUCCESS)
+}
+
+/// Generates one puzzle from the seed, with the shape, symmetry, difficulty, and symbols from the options.
+fn generate_puzzle(options: &Options, seed: u64) -> Result<Board, CliError> {
+    let (rows_per_box, cols_per_box) = shape_or(options, (3, 3));
+    let mut generator = PuzzleGenerator::with_seed(seed).symmetry(options.symmetry);
+    for variant in options.variants.iter() {
+        generator = generator.shared_constraint(variant.constraint());
+    }
+    if let Some(regions) = &options.regions {
+        generator = generator.regions(regions.clone());
+    }
+    if let Some(difficulty) = options.difficulty {
+        generator = generator.difficulty(difficulty);
+    }
+    let mut puzzle = generator.try_generate_puzzle(rows_per_box, cols_per_box).map_err(|error| CliError::Usage(error.to_string()))?;
+    if let Some(symbols) = symbols_for(options, rows_per_box * cols_per_box).map_err(CliError::Usage)? {
+        puzzle.set_symbols(symbols).map_err(|error| CliError::Usage(error.to_string()))?;
+    }
+    Ok(puzzle)
+}
+
+/// Generates one killer puzzle from the seed, with the shape, cage sizes, givens, difficulty, and symbols from the options,
+/// along with its rating.
+fn generate_killer(options: &Options, seed: u64) -> Result<(Board, Rating), CliError> {
+    let (rows_per_box, cols_per_box) = shape_or(options, (3, 3));
+    let mut generator = KillerGenerator::with_seed(seed);
+    if let Some((min, max)) = options.cage_sizes {
+        generator = generator.cage_sizes(min, max);
+    }
+    if let Some(givens) = options.givens {
+        generator = generator.givens(givens);
+    }
+    if let Some(difficulty) = options.difficulty {
+        generator = generator.difficulty(difficulty);
+    }
+    let (mut puzzle, rating) = generator.try_generate_puzzle(rows_per_box, cols_per_box).map_err(|error| CliError::Usage(error.to_string()))?;
+    if let Some(symbols) = symbols_for(options, rows_per_box * cols_per_box).map_err(CliError::Usage)? {
+        puzzle.set_symbols(symbols).map_err(|error| CliError::Usage(error.to_string()))?;
+    }
+    Ok((puzzle, rating))
+}
+
+/// Generates one Kropki puzzle from the seed, with the shape, negative rule, and symbols from the options.
+fn generate_kropki(options: &Options, seed: u64) -> Result<Board, CliError> {
+    let (rows_per_box, cols_per_box) = shape_or(options, (3, 3));
+    let mut generator = KropkiGenerator::with_seed(seed).all_dots_given(options.all_dots);
+    let mut puzzle = generator.try_generate_puzzle(rows_per_box, cols_per_box).map_err(|error| CliError::Usage(error.to_string()))?;
+    if let Some(symbols) = symbols_for(options, rows_per_box * cols_per_box).map_err(CliError::Usage)? {
+        puzzle.set_symbols(symbols).map_err(|error| CliError::Usage(error.to_string()))?;
+    }
+    Ok(puzzle)
+}
+
+fn rate(options: &Options, out: &mut impl Write) -> Result<u8, CliError> {
+    let mut code = EXIT_SUCCESS;
+    for (index, line) in read_puzzles(options)?.iter().enumerate() {
+        let number = index + 1;
+        let rating = parse_puzzle(options, number, line)?.rate();
+        if rating.is_none() {
+            code = EXIT_PUZZLE_FAILED;
+        }
+
+        match (options.format, rating) {
+            (Format::Human, Some(rating)) => writeln!(out, "Puzzle {number}: {} ({} guesses)", rating.difficulty, rating.guesses)?,
+            (Format::Human, None) => writeln!(out, "Puzzle {number}: no solution")?,
+            (Format::Json, rating) => {
+                let fields = [
+                    ("puzzle", number.to_string()),
+                    ("input", json_string(line)),
+                    ("difficulty", rating.map_or("null".to_string(), |rating| json_string(rating.difficulty.name()))),
+                    ("guesses", rating.map_or("null".to_string(), |rating| rating.guesses.to_string())),
+                ];
+                writeln!(out, "{}", json_object(&fields))?;
+            }
+        }
+    }
+    Ok(code)
+}
+
+fn validate(options: &Options, out: &mut impl Write) -> Result<u8, CliError> {
+    let mut code = EXIT_SUCCESS;
+    for (index, line) in read_puzzles(options)?.iter().enumerate() {
+        let number = index + 1;
+        let board = parse_puzzle(options, number, line)?;
+        let conflicts = board.conflicting_cells();
+        //Only look for solutions if the givens are consistent, and stop counting at 2.
+        let solutions = if conflicts.is_empty() { board.count_solutions(2) } else { 0 };
+        let valid = conflicts.is_empty() && solutions == 1;
+        if !valid {
+            code = EXIT_PUZZLE_FAILED;
+        }
+
+        match options.format {
+            Format::Human => {
+                let verdict = if !conflicts.is_empty() {
+                    let cells: Vec<String> = conflicts.iter().map(|&location| cell_name(location)).collect();
+                    format!("invalid, conflicting givens at {}", cells.join(", "))
+                }
+                else if solutions == 0 {
+                    "invalid, no solution".to_string()
+                }
+                else if solutions > 1 {
+                    "invalid, more than one solution".to_string()
+                }
+                else {
+                    "valid".to_string()
+                };
+                writeln!(out, "Puzzle {number}: {verdict}")?;
+            }
+            Format::Json => {
+                let cells: Vec<String> = conflicts
+                    .iter()
+                    .filter_map(|location| match location {
+                        Location::Valid(r, c) => Some(json_array(&[r.to_string(), c.to_string()])),
+                        _ => None,
+                    })
+                    .collect();
+                let fields = [
+                    ("puzzle", number.to_string()),
+                    ("input", json_string(line)),
+                    ("valid", valid.to_string()),
+                    ("conflicts", json_array(&cells)),
+                    ("solutions", solutions.to_string()),
+                ];
+                writeln!(out, "{}", json_object(&fields))?;
+            }
+        }
+    }
+    Ok(code)
+}
+
+fn bench(options: &Options, out: &mut impl Write) -> Result<u8, CliError> {
+    let (rows_per_box, cols_per_box) = shape_or(options, (3, 4));
+    let num_puzzles = options.count.unwrap_or(1000).max(1);
+    let mut generator = PuzzleGenerator::new();
+    for variant in options.variants.iter() {
+        generator = generator.shared_constraint(variant.constraint());
+    }
+    if let Some(regions) = &options.regions {
+        generator = generator.regions(regions.clone());
+    }
+    let mut fast_total = 0;
+    for puzzle_num in 0..num_puzzles {
+        let mut board = generator.try_generate_puzzle(rows_per_box, cols_per_box).map_err(|error| CliError::Usage(error.to_string()))?;
+        if options.format == Format::Human && puzzle_num % (num_puzzles / 100).max(1) == 0 {
+            writeln!(out, "Solved {puzzle_num} so far...")?;
+        }
+        let start = Instant::now();
+        board.fast_solve();
+        fast_total += start.elapsed().as_nanos();
+    }
+    let fast_average = fast_total / num_puzzles as u128;
+
+    match options.format {
+        Format::Human => writeln!(out, "Fast is {} nanoseconds on average.", format_large_number(fast_average))?,
+        Format::Json => writeln!(out, "{}", json_object(&[("puzzles", num_puzzles.to_string()), ("average_nanoseconds", fast_average.to_string())]))?,
+    }
+    Ok(EXIT_SUCCESS)
+}
+
+fn booklet(options: &Options, out: &mut impl Write) -> Result<u8, CliError> {
+    let mut booklet = Booklet::new(options.title.as_deref().unwrap_or("Sudoku")).paper_size(options.paper_size);
+    if options.puzzles.is_empty() && options.files.is_empty() && options.cages.is_none() {
+        let base_seed = options.seed.unwrap_or_else(rand::random);
+        for index in 0..options.count.unwrap_or(1) {
+            let seed = base_seed.wrapping_add(index as u64);
+            booklet.add_puzzle(generate_puzzle(options, seed)?, Some(seed));
+        }
+    }
+    else {
+        for (index, line) in read_puzzles(options)?.iter().enumerate() {
+            booklet.add_puzzle(parse_puzzle(options, index + 1, line)?, None);
+        }
+    }
+
+    let html = booklet.to_html();
+    match &options.output {
+        Some(path) => fs::write(path, html).map_err(|error| CliError::Input(format!("couldn't write {path}: {error}")))?,
+        None => write!(out, "{html}")?,
+    }
+    //Like solve, fail if any puzzle couldn't be solved, though the booklet is still written.
+    let all_solvable = booklet.puzzles().iter().all(|entry| entry.difficulty.is_some());
+    Ok(if all_solvable { EXIT_SUCCESS } else { EXIT_PUZZLE_FAILED })
+}
+
+/// Quotes and escapes a string for JSON output.
+fn json_string(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() + 2);
+    output.push('"');
+    for c in input.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// Writes a JSON object with the fields in the given order. Each value must already be JSON,
+/// e.g. from `json_string`, `json_array`, or a number's `to_string`.
+fn json_object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields.iter().map(|(name, value)| format!("{}:{value}", json_string(name))).collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Writes a JSON array of values that are already JSON.
+fn json_array(values: &[String]) -> String {
+    format!("[{}]", values.join(","))
+}
+
+fn format_large_number(input: u128) -> String {
+    let mut output = format!("{input}");
+    let len = output.len();
+    if len >= 3 {
+        for i in (1..len-2).rev().step_by(3) {
+            output.insert(i, ',');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Options, CliError> {
+        let args: Vec<String> = args.split_whitespace().map(str::to_string).collect();
+        parse_args(&args)
+    }
+
+    fn usage_error(args: &str) -> String {
+        match parse(args) {
+            Err(CliError::Usage(message)) => message,
+            _ => panic!("expected a usage error for '{args}'"),
+        }
+    }
+
+    #[test]
+    fn reads_commands_and_options() {
+        let Ok(options) = parse("generate -n 3 --seed 7 --difficulty hard --symmetry rotational -s 2x3") else { panic!("generate should parse") };
+        assert!(options.command == Command::Generate);
+        assert_eq!((options.count, options.seed, options.shape), (Some(3), Some(7), Some((2, 3))));
+        assert_eq!(options.difficulty, Some(Difficulty::Hard));
+        assert!(options.symmetry != Symmetry::None);
+
+        let Ok(options) = parse("solve 1.34 34.2 --format json --symbols hex") else { panic!("solve should parse") };
+        assert_eq!(options.puzzles, ["1.34", "34.2"]);
+        assert!(options.format == Format::Json && matches!(options.symbols, Symbols::Hex));
+
+        assert!(parse("rate --help 1234").is_ok_and(|options| options.command == Command::Help));
+    }
+
+    #[test]
+    fn rejects_bad_command_lines() {
+        assert_eq!(usage_error(""), "no command given");
+        assert_eq!(usage_error("play"), "unknown command 'play'");
+        assert_eq!(usage_error("generate 1234"), "unexpected argument '1234'");
+        assert_eq!(usage_error("solve --seed 1"), "unexpected option '--seed'");
+        assert_eq!(usage_error("generate -n"), "-n needs a value");
+        assert_eq!(usage_error("generate -n many"), "-n expects a number, not 'many'");
+        assert_eq!(usage_error("generate --givens 20"), "--cage-size and --givens need --killer");
+        assert_eq!(usage_error("generate --killer --cage-size 3-2"), "--cage-size expects MIN-MAX with 1 <= MIN <= MAX, not '3-2'");
+        assert!(usage_error("solve -s 0x3").contains("not supported"));
+    }
+
+    #[test]
+    fn ranges_and_shapes() {
+        assert!(matches!(parse_range("--cage-size", "2-4"), Ok((2, 4))));
+        assert!(matches!(parse_range("--cage-size", "3"), Ok((3, 3))));
+        assert!(matches!(parse_range("--cage-size", "0-4"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_shape("3X2"), Ok((3, 2))));
+        assert!(matches!(parse_shape("9"), Ok((3, 3))));
+        assert!(matches!(parse_shape("6"), Ok(shape) if shape == box_shape(6)));
+    }
+
+    #[test]
+    fn splits_lines_and_grids_into_puzzles() {
+        let mut puzzles = Vec::new();
+        split_puzzles("# a comment\n1.34\n\n+--+--+\n|1.|34|\n+--+--+\n34.2\n", &mut puzzles);
+        assert_eq!(puzzles, ["1.34", "+--+--+\n|1.|34|\n+--+--+\n", "34.2"]);
+    }
+
+    #[test]
+    fn reads_puzzles_with_the_options() {
+        let Ok(options) = parse("solve --symbols WORD") else { panic!("solve should parse") };
+        let board = parse_puzzle(&options, 1, "W.RD\nRD.O\nO.DR\nDR.W").unwrap_or_else(|_| panic!("puzzle should parse"));
+        assert_eq!(board.symbol_at(Location::Valid(0, 3)), Some('D'));
+
+        let Ok(options) = parse("solve") else { panic!("solve should parse") };
+        assert!(matches!(parse_puzzle(&options, 2, "1.34\n34.2\n2.43\n43.x"), Err(CliError::Input(message)) if message.starts_with("puzzle 2: ")));
+    }
+
+    #[test]
+    fn output_formatting() {
+        assert_eq!(cell_name(Location::Valid(0, 4)), "r1c5");
+        assert_eq!(json_string("a \"b\"\\\n"), r#""a \"b\"\\\u000a""#);
+        assert_eq!(json_object(&[("a", "1".to_string()), ("b", json_array(&[json_string("x"), "null".to_string()]))]), r#"{"a":1,"b":["x",null]}"#);
+        assert_eq!(format_large_number(12), "12");
+        assert_eq!(format_large_number(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn commands_write_json_lines() {
+        let Ok(options) = parse("rate --format json 1.3434.22.4343.1 11..............") else { panic!("rate should parse") };
+        let mut out = Vec::new();
+        assert!(matches!(run_command(&options, &mut out), Ok(EXIT_PUZZLE_FAILED)));
+        let lines: Vec<String> = String::from_utf8_lossy(&out).lines().map(str::to_string).collect();
+        assert_eq!(lines[0], r#"{"puzzle":1,"input":"1.3434.22.4343.1","difficulty":"easy","guesses":0}"#);
+        assert_eq!(lines[1], r#"{"puzzle":2,"input":"11..............","difficulty":null,"guesses":null}"#);
+    }
+
+    /// A reader that has gone away, like `head` after it's read enough.
+    struct ClosedPipe;
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn output_errors_stop_the_command() {
+        let Ok(options) = parse("solve 1.3434.22.4343.1 1.3434.22.4343.1") else { panic!("solve should parse") };
+        assert!(matches!(run_command(&options, &mut ClosedPipe), Err(CliError::Output(error)) if error.kind() == io::ErrorKind::BrokenPipe));
+    }
+}
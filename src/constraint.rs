@@ -0,0 +1,167 @@
+use std::any::Any;
+
+use crate::{board::Board, location::Location};
+
+/// A rule the digits of a `Board` must follow, on top of every cell holding one digit.
+///
+/// Most rules are houses: groups of cells that can't repeat a digit, like the rows, cols, and boxes of a classic board.
+/// The solver eliminates candidates, finds hidden singles (in houses with a cell per digit), and checks for
+/// repeats in every house of every constraint, so a rule made only of houses just needs `houses`.
+/// Other rules remove candidates themselves in `propagate` and report broken rules in `has_contradiction`
+/// (and which cells break them in `conflicting_cells`).
+///
+/// A board starts with `RowConstraint`, `ColConstraint`, and `BoxConstraint`; see `Board::try_add_constraint`.
+/// `Board::constraint` finds a board's constraint of a given type, e.g. to draw it.
+pub trait Constraint: Any + Send + Sync {
+    /// Short lowercase name, e.g. "rows".
+    fn name(&self) -> &str;
+
+    /// The groups of cells that can't repeat a digit, for a board with the given size and box shape.
+    /// Houses can have at most one cell per digit.
+    fn houses(&self, _board: &Board) -> Vec<Vec<Location>> {
+        Vec::new()
+    }
+
+    /// Any other cells the rule is about, which `Board::try_add_constraint` checks are on the board like the cells of its houses.
+    fn cells(&self, _board: &Board) -> Vec<Location> {
+        Vec::new()
+    }
+
+    /// Removes candidates that would break the rule, given the board's other candidates.
+    /// Returns true if it removed anything, false if it didn't, and `None` if the board is unsolvable in its current state.
+    /// Cells left with one candidate are marked solved by the solver afterwards.
+    fn propagate(&self, _board: &mut Board) -> Option<bool> {
+        Some(false)
+    }
+
+    /// Returns true iff the board's solved cells break the rule in a way its houses don't show.
+    fn has_contradiction(&self, _board: &Board) -> bool {
+        false
+    }
+
+    /// The solved cells breaking the rule in a way its houses don't show, e.g. both cells of a broken Kropki dot.
+    /// `Board::conflicting_cells` adds these to the cells that repeat a digit in a house.
+    fn conflicting_cells(&self, _board: &Board) -> Vec<Location> {
+        Vec::new()
+    }
+}
+
+/// Every row has each digit once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RowConstraint;
+
+/// Every col has each digit once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ColConstraint;
+
+/// Every `rows_per_box` x `cols_per_box` box has each digit once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BoxConstraint;
+
+impl Constraint for RowConstraint {
+    fn name(&self) -> &str {
+        "rows"
+    }
+
+    fn houses(&self, board: &Board) -> Vec<Vec<Location>> {
+        let size = board.cells_per_set();
+        (0..size).map(|r| (0..size).map(|c| Location::Valid(r, c)).collect()).collect()
+    }
+}
+
+impl Constraint for ColConstraint {
+    fn name(&self) -> &str {
+        "cols"
+    }
+
+    fn houses(&self, board: &Board) -> Vec<Vec<Location>> {
+        let size = board.cells_per_set();
+        (0..size).map(|c| (0..size).map(|r| Location::Valid(r, c)).collect()).collect()
+    }
+}
+
+impl Constraint for BoxConstraint {
+    fn name(&self) -> &str {
+        "boxes"
+    }
+
+    fn houses(&self, board: &Board) -> Vec<Vec<Location>> {
+        let (rows_per_box, cols_per_box) = (board.rows_per_box(), board.cols_per_box());
+        (0..board.cells_per_set())
+            .map(|b| {
+                let (top, left) = (rows_per_box * (b / rows_per_box), cols_per_box * (b % rows_per_box));
+                (0..rows_per_box * cols_per_box).map(|i| Location::Valid(top + i / cols_per_box, left + i % cols_per_box)).collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SudokuError;
+
+    /// The first cell is odd, as a rule the solver only learns through `propagate`.
+    struct OddCorner;
+
+    impl Constraint for OddCorner {
+        fn name(&self) -> &str {
+            "odd corner"
+        }
+
+        fn propagate(&self, board: &mut Board) -> Option<bool> {
+            let cell = board.get_mut(Location::Valid(0, 0));
+            let even: Vec<usize> = (1..cell.possibilities.len()).step_by(2).filter(|&digit| cell.possibilities[digit]).collect();
+            for &digit in even.iter() {
+                cell.possibilities[digit] = false;
+            }
+            cell.possibilities.contains(&true).then_some(!even.is_empty())
+        }
+    }
+
+    struct ExtraHouse(Vec<Location>);
+
+    impl Constraint for ExtraHouse {
+        fn name(&self) -> &str {
+            "extra house"
+        }
+
+        fn houses(&self, _board: &Board) -> Vec<Vec<Location>> {
+            vec![self.0.clone()]
+        }
+    }
+
+    #[test]
+    fn boxes_match_the_box_shape() {
+        let board = Board::new(2, 3);
+        let boxes = BoxConstraint.houses(&board);
+        assert_eq!(boxes.len(), 6);
+        assert_eq!(boxes[1], vec![
+            Location::Valid(0, 3), Location::Valid(0, 4), Location::Valid(0, 5),
+            Location::Valid(1, 3), Location::Valid(1, 4), Location::Valid(1, 5),
+        ]);
+        for (house, cells) in boxes.iter().enumerate() {
+            assert!(cells.iter().all(|&location| board.geometry().index(location).map(|cell| board.geometry().box_of(cell)) == Some(house)));
+        }
+    }
+
+    #[test]
+    fn added_houses_must_fit_the_board() {
+        let mut board = Board::new(2, 2);
+        let house = |cells: &[(usize, usize)]| ExtraHouse(cells.iter().map(|&(r, c)| Location::Valid(r, c)).collect());
+        assert_eq!(board.try_add_constraint(house(&[(0, 0), (4, 0)])), Err(SudokuError::InvalidLocation(Location::Valid(4, 0))));
+        assert_eq!(board.try_add_constraint(house(&[(0, 0), (0, 0)])), Err(SudokuError::RepeatedCell(Location::Valid(0, 0))));
+        assert_eq!(board.try_add_constraint(house(&[(0, 0), (1, 1), (2, 2), (3, 3), (3, 0)])), Err(SudokuError::HouseTooLarge { expected: 4, found: 5 }));
+        assert_eq!(board.constraints().len(), 3);
+    }
+
+    #[test]
+    fn board_propagates_custom_constraints() {
+        //Blank apart from the first cell, which can only be 1 or 2.
+        let mut board = Board::new(2, 2);
+        board.get_mut(Location::Valid(0, 0)).possibilities = vec![true, true, false, false];
+        board.add_constraint(OddCorner);
+        assert_eq!(board.propagate_constraints(), Some(true));
+        assert_eq!(board.get(Location::Valid(0, 0)).possibilities, vec![true, false, false, false]);
+    }
+}
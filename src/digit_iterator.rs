@@ -1,4 +1,6 @@
-use crate::{board::Board, cell::Cell, digit_set::DigitSet, index_iterator::IndexIterator};
+use std::sync::Arc;
+
+use crate::{board::Board, cell::Cell, digit_set::DigitSet, geometry::Geometry, index_iterator::IndexIterator};
 
 /// Wrapper for an IndexIterator which returns references to `Cell`s instead of indices.
 pub struct DigitIterator<'a> {
@@ -8,10 +10,10 @@ pub struct DigitIterator<'a> {
 
 impl<'a> DigitIterator<'a> {
     /// Creates a new DigitIterator containing a new IndexIterator over the specified board and set.
-    pub fn new(board: &'a Board, set: DigitSet, cells_per_set: usize) -> Self {
+    pub fn new(board: &'a Board, set: DigitSet, geometry: Arc<Geometry>) -> Self {
         Self {
             board,
-            index_iterator: IndexIterator::new(set, cells_per_set),
+            index_iterator: IndexIterator::new(set, geometry),
         }
     }
 }
@@ -22,4 +24,4 @@ impl<'a> Iterator for DigitIterator<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         self.index_iterator.next().map(|location| self.board.get(location))
     }
-}
\ No newline at end of file
+}
@@ -1,11 +1,14 @@
 use std::fmt::Display;
 
 ///A set of digits, either a set that must have the digits 1-9 once each or the whole board
+///`House(size, index)` is the board's house with that index (see `Geometry`), which can be smaller than a row.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DigitSet {
     Row(usize, usize),
     Col(usize, usize),
     Box(usize, usize),
+    House(usize, usize),
     All(usize),
 }
 
@@ -15,6 +18,7 @@ impl DigitSet {
             Self::Row(size, _) => *size,
             Self::Col(size, _) => *size,
             Self::Box(size, _) => *size,
+            Self::House(size, _) => *size,
             Self::All(size) => size*size,
         }
     }
@@ -26,6 +30,7 @@ impl Display for DigitSet {
             Self::Row(_, r) => write!(f, "row {r}"),
             Self::Col(_, c) => write!(f, "col {c}"),
             Self::Box(_, b) => write!(f, "box {b}"),
+            Self::House(_, house) => write!(f, "house {house}"),
             Self::All(_) => write!(f, "all cells"),
         }
     }
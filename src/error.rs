@@ -0,0 +1,208 @@
+use std::fmt::Display;
+
+use crate::location::Location;
+
+/// Everything that can go wrong when building, reading, or changing a `Board` from outside the solver.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SudokuError {
+    /// The box shape would give a board with no cells, or more than `MAX_SIZE` cells per set.
+    UnsupportedShape { rows_per_box: usize, cols_per_box: usize },
+    /// The location isn't a cell on the board.
+    InvalidLocation(Location),
+    /// The cell is a clue of the puzzle, so a player can't change it.
+    GivenCell(Location),
+    /// The char isn't one of the digits of a board with `size` cells per set.
+    InvalidChar { size: usize, input: char },
+    /// The digit index is too large for a board with `size` cells per set.
+    InvalidDigit { size: usize, digit: usize },
+    /// A `Cell` with the wrong number of possibilities for the board.
+    WrongCellSize { expected: usize, found: usize },
+    /// A cell read from outside has no candidates, or is marked solved without exactly one.
+    InconsistentCell(Location),
+    /// A row of the input had the wrong number of cells.
+    WrongRowLength { row: usize, expected: usize, found: usize },
+    /// The input had a number of cells that isn't the expected one, or (if `expected` is `None`)
+    /// isn't the square of any supported size.
+    WrongCellCount { expected: Option<usize>, found: usize },
+    /// The input had a char that isn't a digit or blank, at the given (1-based) line and column.
+    UnexpectedChar { line: usize, column: usize, input: char },
+    /// A symbol set had the wrong number of symbols for the board, or (if `expected` is `None`) an unsupported number.
+    WrongSymbolCount { expected: Option<usize>, found: usize },
+    /// The char can't be used as a symbol because it means something else when reading or writing boards.
+    InvalidSymbol(char),
+    /// The char appears more than once in a symbol set.
+    DuplicateSymbol(char),
+    /// The thick separators of a grid don't divide it into boxes of the same size.
+    UnevenSeparators,
+    /// A constraint has a house with more cells than the board has digits.
+    HouseTooLarge { expected: usize, found: usize },
+    /// A constraint has a house with the same cell in it twice.
+    RepeatedCell(Location),
+    /// No way of filling in the board follows all of its constraints, e.g. diagonals on a 3x3 board.
+    Unsatisfiable,
+    /// A generator ran out of attempts without making a puzzle.
+    NoPuzzleGenerated,
+    /// A jigsaw region, given by one of its cells, has the wrong number of cells.
+    WrongRegionSize { location: Location, expected: usize, found: usize },
+    /// A jigsaw region, given by one of its cells, is in more than one piece.
+    DisconnectedRegion(Location),
+    /// The cell is in more than one killer cage.
+    OverlappingCages(Location),
+    /// The cage at the cell touches so many other cages that there's no label left to draw it with.
+    NoCageLabel(Location),
+    /// The number of killer cage sums doesn't match the number of cages.
+    WrongSumCount { expected: usize, found: usize },
+    /// The cell doesn't touch the cell before it on its thermometer.
+    BrokenThermometer(Location),
+    /// An arrow has no circle or no cells along it.
+    EmptyArrow,
+    /// The cell doesn't touch the cell before it on its arrow (or the circle, for the arrow's first cell).
+    BrokenArrow(Location),
+    /// A line of arrows has no `=` between the circle and the cells, at the given (1-based) line.
+    UnknownArrow { line: usize, input: String },
+    /// A Kropki dot is between two cells that don't share a side.
+    DotNotBetweenNeighbors(Location, Location),
+    /// A line of Kropki dots isn't a color and two cells, at the given (1-based) line.
+    UnknownDot { line: usize, input: String },
+}
+
+impl Display for SudokuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedShape { rows_per_box, cols_per_box } => {
+                write!(f, "{rows_per_box} x {cols_per_box} boxes are not supported")
+            }
+            Self::InvalidLocation(location) => {
+                write!(f, "{location} is not on the board")
+            }
+            Self::GivenCell(location) => {
+                write!(f, "{location} is a given")
+            }
+            Self::InvalidChar { size, input } => {
+                write!(f, "'{input}' is not a digit of a {size} x {size} board")
+            }
+            Self::InvalidDigit { size, digit } => {
+                write!(f, "digit index {digit} is too large for a {size} x {size} board")
+            }
+            Self::WrongCellSize { expected, found } => {
+                write!(f, "cell has {found} possibilities but the board needs {expected}")
+            }
+            Self::InconsistentCell(location) => {
+                write!(f, "{location} has no candidates, or is marked solved without exactly one")
+            }
+            Self::WrongRowLength { row, expected, found } => {
+                write!(f, "row {row} has {found} cells but should have {expected}")
+            }
+            Self::WrongCellCount { expected: Some(expected), found } => {
+                write!(f, "found {found} cells but expected {expected}")
+            }
+            Self::WrongCellCount { expected: None, found } => {
+                write!(f, "found {found} cells, which isn't the square of a supported size")
+            }
+            Self::UnexpectedChar { line, column, input } => {
+                write!(f, "unexpected '{input}' at line {line}, column {column}")
+            }
+            Self::WrongSymbolCount { expected: Some(expected), found } => {
+                write!(f, "found {found} symbols but the board needs {expected}")
+            }
+            Self::WrongSymbolCount { expected: None, found } => {
+                write!(f, "{found} symbols is not a supported number")
+            }
+            Self::InvalidSymbol(symbol) => {
+                write!(f, "'{symbol}' can't be used as a symbol")
+            }
+            Self::DuplicateSymbol(symbol) => {
+                write!(f, "'{symbol}' is used as a symbol more than once")
+            }
+            Self::UnevenSeparators => {
+                write!(f, "the grid's thick separators don't divide it into equal boxes")
+            }
+            Self::HouseTooLarge { expected, found } => {
+                write!(f, "a house has {found} cells but can have at most {expected}")
+            }
+            Self::RepeatedCell(location) => {
+                write!(f, "{location} is in the same house twice")
+            }
+            Self::Unsatisfiable => {
+                write!(f, "no board of this size can follow all of its constraints")
+            }
+            Self::NoPuzzleGenerated => {
+                write!(f, "no puzzle was generated")
+            }
+            Self::WrongRegionSize { location, expected, found } => {
+                write!(f, "the region at {location} has {found} cells but should have {expected}")
+            }
+            Self::DisconnectedRegion(location) => {
+                write!(f, "the region at {location} isn't connected")
+            }
+            Self::OverlappingCages(location) => {
+                write!(f, "{location} is in more than one cage")
+            }
+            Self::NoCageLabel(location) => {
+                write!(f, "the cage at {location} touches too many other cages to be given a label")
+            }
+            Self::WrongSumCount { expected, found } => {
+                write!(f, "found {found} cage sums but there are {expected} cages")
+            }
+            Self::BrokenThermometer(location) => {
+                write!(f, "{location} doesn't touch the cell before it on its thermometer")
+            }
+            Self::EmptyArrow => {
+                write!(f, "an arrow needs a circle, then '=' and the cells along it")
+            }
+            Self::BrokenArrow(location) => {
+                write!(f, "{location} doesn't touch the cell before it on its arrow")
+            }
+            Self::UnknownArrow { line, input } => {
+                write!(f, "can't read '{input}' at line {line} as an arrow (expected the circle's cells, then '=' and the cells along it)")
+            }
+            Self::DotNotBetweenNeighbors(a, b) => {
+                write!(f, "{a} and {b} don't share a side, so there can't be a dot between them")
+            }
+            Self::UnknownDot { line, input } => {
+                write!(f, "can't read '{input}' at line {line} as a dot (expected white or black, then two cells)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SudokuError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, cell::Cell, puzzle_generator::PuzzleGenerator};
+
+    #[test]
+    fn fallible_constructors_report_what_went_wrong() {
+        assert_eq!(Board::try_new(0, 3).err(), Some(SudokuError::UnsupportedShape { rows_per_box: 0, cols_per_box: 3 }));
+        assert_eq!(Board::try_new(9, 8).err(), Some(SudokuError::UnsupportedShape { rows_per_box: 9, cols_per_box: 8 }));
+        assert_eq!(Cell::try_new_single_digit(4, 4).err(), Some(SudokuError::InvalidDigit { size: 4, digit: 4 }));
+        assert_eq!(Cell::try_new_single_char(4, '5').err(), Some(SudokuError::InvalidChar { size: 4, input: '5' }));
+        assert_eq!(
+            PuzzleGenerator::with_seed(1).try_generate_puzzle(usize::MAX, 2).err(),
+            Some(SudokuError::UnsupportedShape { rows_per_box: usize::MAX, cols_per_box: 2 })
+        );
+    }
+
+    #[test]
+    fn fallible_board_access_reports_what_went_wrong() {
+        let mut board = Board::new(2, 2);
+        assert_eq!(board.try_get(Location::Valid(4, 0)).err(), Some(SudokuError::InvalidLocation(Location::Valid(4, 0))));
+        assert_eq!(board.try_set(Location::Valid(0, 0), Cell::new(9)), Err(SudokuError::WrongCellSize { expected: 4, found: 9 }));
+        assert_eq!(board.try_set(Location::Invalid, Cell::new(4)), Err(SudokuError::InvalidLocation(Location::Invalid)));
+        assert_eq!(Board::try_from_chars(&[['1', '2'], ['3', '4'], ['1', '2']]).err(), Some(SudokuError::WrongRowLength { row: 0, expected: 3, found: 2 }));
+    }
+
+    #[test]
+    fn messages() {
+        assert_eq!(SudokuError::UnsupportedShape { rows_per_box: 0, cols_per_box: 3 }.to_string(), "0 x 3 boxes are not supported");
+        assert_eq!(SudokuError::GivenCell(Location::Valid(1, 2)).to_string(), "(1, 2) is a given");
+        assert_eq!(SudokuError::WrongCellCount { expected: Some(81), found: 80 }.to_string(), "found 80 cells but expected 81");
+        assert_eq!(SudokuError::WrongCellCount { expected: None, found: 80 }.to_string(), "found 80 cells, which isn't the square of a supported size");
+        assert_eq!(SudokuError::UnexpectedChar { line: 2, column: 5, input: 'x' }.to_string(), "unexpected 'x' at line 2, column 5");
+        assert_eq!(SudokuError::NoPuzzleGenerated.to_string(), "no puzzle was generated");
+        assert_eq!(SudokuError::NoCageLabel(Location::Valid(3, 4)).to_string(), "the cage at (3, 4) touches too many other cages to be given a label");
+    }
+}
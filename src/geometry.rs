@@ -0,0 +1,271 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock}
+};
+
+use crate::{digit_set::DigitSet, location::Location};
+
+type GeometryCache = Mutex<HashMap<(usize, usize), Arc<Geometry>>>;
+
+/// Precomputed lookup tables for one board shape and set of houses.
+///
+/// Cells are numbered in row-major order, so cell `i` is at row `i / size` and col `i % size`.
+/// Houses are the groups of cells that can't repeat a digit. For a classic board they're numbered
+/// with every row first, then every col, then every box, so row `r` is house `r`, col `c` is house `size + c`
+/// and box `b` is house `2*size + b`; boards with other constraints have the houses of each constraint in turn.
+#[derive(Debug, Default)]
+pub struct Geometry {
+    rows_per_box: usize,
+    cols_per_box: usize,
+    size: usize,
+    cell_rows: Vec<usize>,
+    cell_cols: Vec<usize>,
+    cell_boxes: Vec<usize>,
+    //Every row, then col, then box, whatever the houses are.
+    sets: Vec<Vec<usize>>,
+    houses: Vec<Vec<usize>>,
+    cell_houses: Vec<Vec<usize>>,
+    all_cells: Vec<usize>,
+    peers: Vec<Vec<usize>>,
+    //Bit matrix of which cells see each other, one row of `words_per_cell` words per cell.
+    sees: Vec<u64>,
+    words_per_cell: usize,
+}
+
+impl Geometry {
+    /// Builds the tables for a classic board whose boxes are `rows_per_box` x `cols_per_box`,
+    /// with the rows, cols, and boxes as its houses.
+    pub fn new(rows_per_box: usize, cols_per_box: usize) -> Self {
+        let mut geometry = Self::with_houses(rows_per_box, cols_per_box, Vec::new());
+        geometry.set_houses(geometry.sets.clone());
+        geometry
+    }
+
+    /// Builds the tables for a board whose boxes are `rows_per_box` x `cols_per_box`, with the given houses
+    /// (lists of cell indices) instead of its rows, cols, and boxes. Cells outside the board are left out.
+    pub fn with_houses(rows_per_box: usize, cols_per_box: usize, houses: Vec<Vec<usize>>) -> Self {
+        let size = rows_per_box * cols_per_box;
+        let num_cells = size * size;
+
+        let mut cell_rows = Vec::with_capacity(num_cells);
+        let mut cell_cols = Vec::with_capacity(num_cells);
+        let mut cell_boxes = Vec::with_capacity(num_cells);
+        let mut sets = vec![Vec::with_capacity(size); 3 * size];
+        for cell in 0..num_cells {
+            let row = cell / size;
+            let col = cell % size;
+            //There are `rows_per_box` boxes across each band of `rows_per_box` rows.
+            let box_index = rows_per_box * (row / rows_per_box) + col / cols_per_box;
+            cell_rows.push(row);
+            cell_cols.push(col);
+            cell_boxes.push(box_index);
+            sets[row].push(cell);
+            sets[size + col].push(cell);
+            sets[2 * size + box_index].push(cell);
+        }
+
+        let mut geometry = Self {
+            rows_per_box,
+            cols_per_box,
+            size,
+            cell_rows,
+            cell_cols,
+            cell_boxes,
+            sets,
+            houses: Vec::new(),
+            cell_houses: Vec::new(),
+            all_cells: (0..num_cells).collect(),
+            peers: Vec::new(),
+            sees: Vec::new(),
+            words_per_cell: num_cells.div_ceil(64),
+        };
+        geometry.set_houses(houses);
+        geometry
+    }
+
+    /// Replaces the houses and rebuilds the tables that depend on them.
+    fn set_houses(&mut self, mut houses: Vec<Vec<usize>>) {
+        let num_cells = self.all_cells.len();
+        let words_per_cell = self.words_per_cell;
+        let mut cell_houses = vec![Vec::new(); num_cells];
+        for (index, house) in houses.iter_mut().enumerate() {
+            house.retain(|&cell| cell < num_cells);
+            for &cell in house.iter() {
+                cell_houses[cell].push(index);
+            }
+        }
+
+        let mut sees = vec![0u64; words_per_cell * num_cells];
+        let mut peers = Vec::with_capacity(num_cells);
+        for (cell, houses_of_cell) in cell_houses.iter().enumerate() {
+            let mut cell_peers = Vec::with_capacity(3 * self.size);
+            for &house in houses_of_cell.iter() {
+                for &other in houses[house].iter() {
+                    let word = cell * words_per_cell + other / 64;
+                    let bit = 1u64 << (other % 64);
+                    if other != cell && sees[word] & bit == 0 {
+                        sees[word] |= bit;
+                        cell_peers.push(other);
+                    }
+                }
+            }
+            cell_peers.sort_unstable();
+            peers.push(cell_peers);
+        }
+
+        self.houses = houses;
+        self.cell_houses = cell_houses;
+        self.peers = peers;
+        self.sees = sees;
+    }
+
+    /// Returns the tables for the given box shape, building them only the first time that shape is requested.
+    pub fn shared(rows_per_box: usize, cols_per_box: usize) -> Arc<Self> {
+        static CACHE: OnceLock<GeometryCache> = OnceLock::new();
+        let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut cache = cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Arc::clone(
+            cache
+            .entry((rows_per_box, cols_per_box))
+            .or_insert_with(|| Arc::new(Self::new(rows_per_box, cols_per_box)))
+        )
+    }
+
+    pub fn rows_per_box(&self) -> usize {
+        self.rows_per_box
+    }
+
+    pub fn cols_per_box(&self) -> usize {
+        self.cols_per_box
+    }
+
+    /// Number of cells in each row, col, and box (and the number of digits).
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.all_cells.len()
+    }
+
+    /// Converts a `Location` to a cell index, or `None` if it isn't a valid location on this board.
+    pub fn index(&self, location: Location) -> Option<usize> {
+        match location {
+            Location::Valid(r, c) if r < self.size && c < self.size => Some(r * self.size + c),
+            _ => None,
+        }
+    }
+
+    /// Converts a cell index to a `Location`.
+    pub fn location(&self, cell: usize) -> Location {
+        Location::Valid(self.cell_rows[cell], self.cell_cols[cell])
+    }
+
+    pub fn row_of(&self, cell: usize) -> usize {
+        self.cell_rows[cell]
+    }
+
+    pub fn col_of(&self, cell: usize) -> usize {
+        self.cell_cols[cell]
+    }
+
+    pub fn box_of(&self, cell: usize) -> usize {
+        self.cell_boxes[cell]
+    }
+
+    /// The indices of the houses containing the cell, in ascending order.
+    pub fn houses_of(&self, cell: usize) -> &[usize] {
+        &self.cell_houses[cell]
+    }
+
+    /// Every house on the board, in the order described above.
+    pub fn houses(&self) -> &[Vec<usize>] {
+        &self.houses
+    }
+
+    /// The cells in the given house.
+    pub fn house(&self, house: usize) -> &[usize] {
+        &self.houses[house]
+    }
+
+    /// The cells in the given set, in the same order `IndexIterator` yields them.
+    /// Sets that aren't on the board have no cells.
+    pub fn cells(&self, set: DigitSet) -> &[usize] {
+        let set = match set {
+            DigitSet::Row(_, r) if r < self.size => r,
+            DigitSet::Col(_, c) if c < self.size => self.size + c,
+            DigitSet::Box(_, b) if b < self.size => 2 * self.size + b,
+            DigitSet::House(_, house) if house < self.houses.len() => return &self.houses[house],
+            DigitSet::All(_) => return &self.all_cells,
+            _ => return &[],
+        };
+        &self.sets[set]
+    }
+
+    /// Every cell sharing a house with the given cell (not including the cell itself), in ascending order.
+    pub fn peers(&self, cell: usize) -> &[usize] {
+        &self.peers[cell]
+    }
+
+    /// Returns true iff the two cells are different and share a house.
+    pub fn sees(&self, a: usize, b: usize) -> bool {
+        self.sees[a * self.words_per_cell + b / 64] & (1u64 << (b % 64)) != 0
+    }
+
+    /// Returns every cell that sees both of the given cells.
+    pub fn common_peers(&self, a: usize, b: usize) -> Vec<usize> {
+        self.peers[a].iter().copied().filter(|&other| self.sees(b, other)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_houses_are_rows_then_cols_then_boxes() {
+        let geometry = Geometry::new(2, 3);
+        assert_eq!((geometry.size(), geometry.num_cells(), geometry.houses().len()), (6, 36, 18));
+
+        let cell = 3 * 6 + 4;
+        assert_eq!(geometry.index(Location::Valid(3, 4)), Some(cell));
+        assert_eq!(geometry.location(cell), Location::Valid(3, 4));
+        assert_eq!((geometry.row_of(cell), geometry.col_of(cell), geometry.box_of(cell)), (3, 4, 3));
+        assert_eq!(geometry.houses_of(cell), &[3, 6 + 4, 12 + 3]);
+        assert_eq!(geometry.house(12 + 3), &[15, 16, 17, 21, 22, 23]);
+        assert_eq!(geometry.cells(DigitSet::Box(6, 3)), geometry.house(12 + 3));
+        assert_eq!(geometry.cells(DigitSet::Col(6, 0)), &[0, 6, 12, 18, 24, 30]);
+        assert!(geometry.cells(DigitSet::Row(6, 6)).is_empty());
+        assert_eq!(geometry.index(Location::Valid(6, 0)), None);
+    }
+
+    #[test]
+    fn peers_and_sees() {
+        let geometry = Geometry::new(3, 3);
+        assert!((0..81).all(|cell| geometry.peers(cell).len() == 20));
+        assert!(geometry.peers(40).windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(geometry.sees(0, 8) && geometry.sees(0, 72) && geometry.sees(0, 20));
+        assert!(!geometry.sees(0, 0) && !geometry.sees(0, 30));
+        //r1c1 and r2c4 both see r1c4-r1c6 (r1c1's row, r2c4's box) and r2c1-r2c3 (r2c4's row, r1c1's box).
+        assert_eq!(geometry.common_peers(0, 12), vec![3, 4, 5, 9, 10, 11]);
+    }
+
+    #[test]
+    fn custom_houses_replace_the_classic_ones() {
+        //Only the main diagonal, plus a house with a cell off the board, which is left out.
+        let geometry = Geometry::with_houses(2, 2, vec![vec![0, 5, 10, 15], vec![3, 16]]);
+        assert_eq!(geometry.houses(), &[vec![0, 5, 10, 15], vec![3]]);
+        assert_eq!(geometry.peers(0), &[5, 10, 15]);
+        assert!(geometry.peers(1).is_empty() && geometry.peers(3).is_empty());
+        assert!(!geometry.sees(0, 1));
+        //Rows, cols, and boxes are still there as sets.
+        assert_eq!(geometry.cells(DigitSet::Row(4, 0)), &[0, 1, 2, 3]);
+        assert_eq!(geometry.cells(DigitSet::House(4, 0)), &[0, 5, 10, 15]);
+    }
+
+    #[test]
+    fn shared_tables_are_built_once_per_shape() {
+        assert!(Arc::ptr_eq(&Geometry::shared(2, 3), &Geometry::shared(2, 3)));
+        assert!(!Arc::ptr_eq(&Geometry::shared(2, 3), &Geometry::shared(3, 2)));
+    }
+}
@@ -0,0 +1,538 @@
+use crate::{
+    board::{Board, MAX_SIZE},
+    constraint::Constraint,
+    error::SudokuError,
+    location::Location
+};
+
+/// The labels `KillerCages::to_text` draws cages with, which neighboring cages never share.
+const CAGE_LABELS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+/// The most steps spent listing the ways to fill one group of cells. Big cages on big boards have too many ways to list,
+/// so those are left to `has_contradiction` until enough of their cells are solved.
+const MAX_COMBINATION_STEPS: usize = 1 << 14;
+
+/// A group of cells whose digits add up to `sum`, with no digit twice.
+/// Digits count as 1 up to the number of digits, whatever symbols draw them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Cage {
+    sum: usize,
+    cells: Vec<Location>,
+}
+
+impl Cage {
+    pub fn new(sum: usize, cells: Vec<Location>) -> Self {
+        Self { sum, cells }
+    }
+
+    pub fn sum(&self) -> usize {
+        self.sum
+    }
+
+    pub fn cells(&self) -> &[Location] {
+        &self.cells
+    }
+
+}
+
+/// What the ways of filling some cells with different digits adding up to a sum have in common.
+struct Fillings {
+    /// For each cell, the digits (as a bit per digit) it has in at least one way.
+    allowed: Vec<u64>,
+    /// The digits every way uses.
+    required: u64,
+}
+
+/// Finds the ways of filling cells with the given candidates (as a bit per digit) with different digits adding up to `sum`,
+/// or returns `None` if there's no way at all. If there are too many ways to list, every candidate is allowed and none required.
+fn fillings(candidates: &[u64], sum: usize, size: usize) -> Option<Fillings> {
+    let mut allowed = vec![0; candidates.len()];
+    let mut required = u64::MAX;
+    let union = candidates.iter().fold(0, |union, &digits| union | digits);
+    let mut steps = MAX_COMBINATION_STEPS;
+    //Each set of digits with the right sum can fill the cells if its digits can be matched up with them.
+    let listed = for_each_combination(union, size, candidates.len(), sum, 0, 0, &mut steps, &mut |combination| {
+        let options: Vec<u64> = candidates.iter().map(|&digits| digits & combination).collect();
+        if !has_matching(&options) {
+            return;
+        }
+        required &= combination;
+        for (index, &digits) in options.iter().enumerate() {
+            for digit in bits(digits & !allowed[index]) {
+                let mut fixed = options.clone();
+                fixed[index] = 1 << digit;
+                if has_matching(&fixed) {
+                    allowed[index] |= 1 << digit;
+                }
+            }
+        }
+    });
+    if !listed {
+        return Some(Fillings { allowed: candidates.to_vec(), required: 0 });
+    }
+    //No way found leaves `required` as every digit.
+    (required != u64::MAX).then_some(Fillings { allowed, required })
+}
+
+/// The cages of a Killer Sudoku: the digits of each cage add up to its sum, without repeating.
+/// A cell can be in at most one cage, and cages with a cell per digit also have each digit once.
+/// Killer puzzles often have no givens at all.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KillerCages {
+    cages: Vec<Cage>,
+}
+
+impl KillerCages {
+    /// Panics if a cell is in more than one cage; see `try_new`.
+    pub fn new(cages: Vec<Cage>) -> Self {
+        match Self::try_new(cages) {
+            Ok(cages) => cages,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Returns an error if a cell is in more than one cage.
+    /// `Board::try_add_constraint` checks the cages fit the board.
+    pub fn try_new(cages: Vec<Cage>) -> Result<Self, SudokuError> {
+        let mut seen = Vec::new();
+        for &location in cages.iter().flat_map(|cage| cage.cells.iter()) {
+            if seen.contains(&location) {
+                return Err(SudokuError::OverlappingCages(location));
+            }
+            seen.push(location);
+        }
+        Ok(Self { cages })
+    }
+
+    /// Reads cages written as a map of the board, with a line per row and a letter per cell naming its cage
+    /// (or `.` for a cell in no cage), then the sums of the cages, like
+    ///
+    /// ```text
+    /// AABBBCDDE
+    /// FFGGBCCDE
+    /// ...
+    /// 3 15 22 4 16 15 ...
+    /// ```
+    ///
+    /// Each group of touching cells (sharing a side) with the same letter is a cage, so cages far apart can share a letter.
+    /// The sums are separated by whitespace, and are in the order the cages' first cells appear row by row.
+    /// Whitespace in the map and blank lines are ignored.
+    pub fn parse(input: &str) -> Result<Self, SudokuError> {
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut sums = Vec::new();
+        for (line, text) in input.lines().enumerate() {
+            let is_sums = text.chars().all(|c| c.is_ascii_digit() || c.is_whitespace());
+            if is_sums {
+                sums.extend(text.split_whitespace().filter_map(|sum| sum.parse::<usize>().ok()));
+                continue;
+            }
+            let mut row = Vec::new();
+            for (column, c) in text.chars().enumerate().filter(|(_, c)| !c.is_whitespace()) {
+                if !c.is_ascii_alphabetic() && c != '.' {
+                    return Err(SudokuError::UnexpectedChar { line: line + 1, column: column + 1, input: c });
+                }
+                row.push(c);
+            }
+            rows.push(row);
+        }
+
+        let size = rows.len();
+        if size == 0 || size > MAX_SIZE {
+            return Err(SudokuError::WrongCellCount { expected: None, found: size * size });
+        }
+        if let Some((r, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != size) {
+            return Err(SudokuError::WrongRowLength { row: r, expected: size, found: row.len() });
+        }
+
+        //Flood fill each cage from its first cell, in reading order.
+        let mut cage_cells: Vec<Vec<Location>> = Vec::new();
+        let mut cage_of = vec![None; size * size];
+        for first in 0..size * size {
+            let label = rows[first / size][first % size];
+            if label == '.' || cage_of[first].is_some() {
+                continue;
+            }
+            cage_of[first] = Some(cage_cells.len());
+            let mut cells = vec![first];
+            let mut next = 0;
+            while let Some(&cell) = cells.get(next) {
+                let (r, c) = (cell / size, cell % size);
+                let neighbors = [(r.wrapping_sub(1), c), (r + 1, c), (r, c.wrapping_sub(1)), (r, c + 1)];
+                for (r, c) in neighbors {
+                    if r < size && c < size && rows[r][c] == label && cage_of[r * size + c].is_none() {
+                        cage_of[r * size + c] = Some(cage_cells.len());
+                        cells.push(r * size + c);
+                    }
+                }
+                next += 1;
+            }
+            cells.sort_unstable();
+            cage_cells.push(cells.into_iter().map(|cell| Location::Valid(cell / size, cell % size)).collect());
+        }
+
+        if sums.len() != cage_cells.len() {
+            return Err(SudokuError::WrongSumCount { expected: cage_cells.len(), found: sums.len() });
+        }
+        Self::try_new(sums.into_iter().zip(cage_cells).map(|(sum, cells)| Cage::new(sum, cells)).collect())
+    }
+
+    /// Writes the cages of a board with the given number of cells per row in the form `parse` reads.
+    /// Cages should be connected, since `parse` splits a cage drawn in several pieces into several cages.
+    /// Panics if a cage touches so many others that no label is left for it; see `try_to_text`.
+    pub fn to_text(&self, size: usize) -> String {
+        self.try_to_text(size).unwrap()
+    }
+
+    /// Like `to_text`, but returns an error if a cage touches so many others that no label is left for it.
+    pub fn try_to_text(&self, size: usize) -> Result<String, SudokuError> {
+        let cell_index = |location: Location| match location {
+            Location::Valid(r, c) if r < size && c < size => Some(r * size + c),
+            _ => None,
+        };
+        let mut order: Vec<&Cage> = self.cages.iter().filter(|cage| cage.cells.iter().any(|&location| cell_index(location).is_some())).collect();
+        order.sort_by_key(|cage| cage.cells.iter().filter_map(|&location| cell_index(location)).min());
+
+        //Give each cage the first label none of the cages next to it have.
+        let labels: Vec<char> = CAGE_LABELS.chars().collect();
+        let mut map = vec!['.'; size * size];
+        for cage in order.iter() {
+            let cells: Vec<usize> = cage.cells.iter().filter_map(|&location| cell_index(location)).collect();
+            let mut taken = Vec::new();
+            for &cell in cells.iter() {
+                let (r, c) = (cell / size, cell % size);
+                let neighbors = [(r.wrapping_sub(1), c), (r + 1, c), (r, c.wrapping_sub(1)), (r, c + 1)];
+                taken.extend(neighbors.into_iter().filter(|&(r, c)| r < size && c < size && !cells.contains(&(r * size + c))).map(|(r, c)| map[r * size + c]));
+            }
+            let label = labels.iter().copied().find(|label| !taken.contains(label)).ok_or(SudokuError::NoCageLabel(cage.cells[0]))?;
+            for cell in cells {
+                map[cell] = label;
+            }
+        }
+
+        let mut text = String::with_capacity(size * (size + 1) + 4 * order.len());
+        for row in map.chunks(size) {
+            text.extend(row.iter());
+            text.push('\n');
+        }
+        let sums: Vec<String> = order.iter().map(|cage| cage.sum.to_string()).collect();
+        text += &sums.join(" ");
+        text.push('\n');
+        Ok(text)
+    }
+
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    /// Returns the cage the location is in, if it's in one.
+    pub fn cage_of(&self, location: Location) -> Option<&Cage> {
+        self.cages.iter().find(|cage| cage.cells.contains(&location))
+    }
+}
+
+impl Constraint for KillerCages {
+    fn name(&self) -> &str {
+        "killer cages"
+    }
+
+    fn houses(&self, _board: &Board) -> Vec<Vec<Location>> {
+        self.cages.iter().map(|cage| cage.cells.clone()).collect()
+    }
+
+    /// Narrows down each cage's cells to the digits of the ways it can be filled, then uses the houses of the board:
+    /// the digits every way of filling a cage uses are taken out of the rest of any house the cage is inside,
+    /// and since each house's digits add up to the same total, so do the cells of a house outside the cages inside it ("innies"),
+    /// and likewise the cells sticking out of a house from the cages covering it ("outies").
+    fn propagate(&self, board: &mut Board) -> Option<bool> {
+        let size = board.cells_per_set();
+        let mut found_something = false;
+        let cages: Vec<(usize, Vec<usize>)> = self
+            .cages
+            .iter()
+            .map(|cage| (cage.sum, cage.cells.iter().filter_map(|&location| board.geometry().index(location)).collect::<Vec<usize>>()))
+            .filter(|(_, cells)| !cells.is_empty())
+            .collect();
+        let mut cage_of = vec![None; board.geometry().num_cells()];
+        for (number, (_, cells)) in cages.iter().enumerate() {
+            for &cell in cells.iter() {
+                cage_of[cell] = Some(number);
+            }
+        }
+
+        let mut required = Vec::with_capacity(cages.len());
+        for (sum, cells) in cages.iter() {
+            let fillings = fillings(&candidates_of(board, cells), *sum, size)?;
+            found_something |= keep_only(board, cells, &fillings.allowed)?;
+            required.push(fillings.required);
+        }
+
+        let total = size * (size + 1) / 2;
+        let houses: Vec<Vec<usize>> = board.geometry().houses().iter().filter(|house| house.len() == size).cloned().collect();
+        for house in houses.iter() {
+            let in_house = |cell: usize| house.contains(&cell);
+            let touching: Vec<usize> = house.iter().filter_map(|&cell| cage_of[cell]).fold(Vec::new(), |mut touching, number| {
+                if !touching.contains(&number) {
+                    touching.push(number);
+                }
+                touching
+            });
+            let (inside, sticking_out): (Vec<usize>, Vec<usize>) = touching.into_iter().partition(|&number| cages[number].1.iter().all(|&cell| in_house(cell)));
+
+            for &number in inside.iter() {
+                let others: Vec<usize> = house.iter().copied().filter(|&cell| cage_of[cell] != Some(number)).collect();
+                found_something |= keep_only(board, &others, &vec![!required[number]; others.len()])?;
+            }
+
+            //With no cage inside, the innies are the whole house, which says nothing new.
+            let innies: Vec<usize> = house.iter().copied().filter(|&cell| !cage_of[cell].is_some_and(|number| inside.contains(&number))).collect();
+            let innie_sum = total.checked_sub(inside.iter().map(|&number| cages[number].0).sum())?;
+            if innies.is_empty() && innie_sum != 0 {
+                return None;
+            }
+            if !inside.is_empty() && !innies.is_empty() {
+                found_something |= keep_only(board, &innies, &fillings(&candidates_of(board, &innies), innie_sum, size)?.allowed)?;
+            }
+
+            //Outies only add up to a known total when cages cover the whole house, and only have different digits if they see each other.
+            if sticking_out.is_empty() || house.iter().any(|&cell| cage_of[cell].is_none()) {
+                continue;
+            }
+            let outies: Vec<usize> = sticking_out.iter().flat_map(|&number| cages[number].1.iter().copied()).filter(|&cell| !in_house(cell)).collect();
+            let outie_sum = inside.iter().chain(sticking_out.iter()).map(|&number| cages[number].0).sum::<usize>().checked_sub(total)?;
+            let distinct = outies.iter().enumerate().all(|(index, &a)| outies[..index].iter().all(|&b| board.geometry().sees(a, b)));
+            if distinct {
+                found_something |= keep_only(board, &outies, &fillings(&candidates_of(board, &outies), outie_sum, size)?.allowed)?;
+            }
+        }
+        Some(found_something)
+    }
+
+    fn has_contradiction(&self, board: &Board) -> bool {
+        self.cages.iter().any(|cage| {
+            let digits: Vec<Option<usize>> = cage.cells.iter().map(|&location| board.get(location).get_single_index()).collect();
+            let total: usize = digits.iter().flatten().map(|digit| digit + 1).sum();
+            total > cage.sum || (digits.iter().all(Option::is_some) && total != cage.sum)
+        })
+    }
+
+    fn conflicting_cells(&self, board: &Board) -> Vec<Location> {
+        let mut conflicting = Vec::new();
+        for cage in self.cages.iter() {
+            let solved: Vec<(Location, usize)> = cage.cells.iter().filter_map(|&location| Some((location, board.get(location).get_single_index()?))).collect();
+            let total: usize = solved.iter().map(|(_, digit)| digit + 1).sum();
+            if total > cage.sum || (solved.len() == cage.cells.len() && total != cage.sum) {
+                conflicting.extend(solved.into_iter().map(|(location, _)| location));
+            }
+        }
+        conflicting
+    }
+}
+
+/// The candidates of each cell, as a bit per digit.
+fn candidates_of(board: &Board, cells: &[usize]) -> Vec<u64> {
+    cells.iter().map(|&cell| digit_bits(&board.get(board.geometry().location(cell)).possibilities)).collect()
+}
+
+/// Takes every digit but the allowed ones out of each cell. Returns whether any were taken out,
+/// or `None` if a cell is left with no candidates.
+fn keep_only(board: &mut Board, cells: &[usize], allowed: &[u64]) -> Option<bool> {
+    let mut found_something = false;
+    for (&cell, &allowed) in cells.iter().zip(allowed.iter()) {
+        let location = board.geometry().location(cell);
+        let digits = digit_bits(&board.get(location).possibilities);
+        if digits & !allowed == 0 {
+            continue;
+        }
+        if digits & allowed == 0 {
+            return None;
+        }
+        let possibilities = &mut board.get_mut(location).possibilities;
+        for digit in bits(digits & !allowed) {
+            possibilities[digit] = false;
+        }
+        found_something = true;
+    }
+    Some(found_something)
+}
+
+/// The possible digits of a cell as a bit per digit.
+pub(crate) fn digit_bits(possibilities: &[bool]) -> u64 {
+    possibilities.iter().enumerate().filter(|(_, &possible)| possible).fold(0, |bits, (digit, _)| bits | 1 << digit)
+}
+
+/// The digits whose bits are set, in increasing order.
+pub(crate) fn bits(mut digits: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        (digits != 0).then(|| {
+            let digit = digits.trailing_zeros() as usize;
+            digits &= digits - 1;
+            digit
+        })
+    })
+}
+
+/// Calls `visit` with every set of `count` digits from `digits`, at least `from`, whose values (digit + 1) add up to `sum`.
+/// Each call takes a step from `steps`, and returns false if they ran out before every set was visited.
+#[allow(clippy::too_many_arguments)]
+fn for_each_combination(digits: u64, size: usize, count: usize, sum: usize, from: usize, chosen: u64, steps: &mut usize, visit: &mut impl FnMut(u64)) -> bool {
+    if *steps == 0 {
+        return false;
+    }
+    *steps -= 1;
+    if count == 0 {
+        if sum == 0 {
+            visit(chosen);
+        }
+        return true;
+    }
+    //The largest total the other `count - 1` digits could add, if they were the largest digits.
+    let most_others = (count - 1) * (2 * size + 2 - count) / 2;
+    for digit in bits(digits & u64::MAX.checked_shl(from as u32).unwrap_or(0)) {
+        let value = digit + 1;
+        //The smallest total, if this and the others were the next digits up.
+        if count * value + count * (count - 1) / 2 > sum {
+            break;
+        }
+        if value + most_others >= sum && !for_each_combination(digits, size, count - 1, sum - value, digit + 1, chosen | 1 << digit, steps, visit) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Returns true if each cell can have a different one of its options (given as a bit per digit).
+fn has_matching(options: &[u64]) -> bool {
+    let mut owners = [usize::MAX; MAX_SIZE];
+    (0..options.len()).all(|cell| find_digit(cell, options, &mut owners, &mut 0))
+}
+
+/// Finds a digit for the cell, moving cells that already have one to other digits if needed.
+fn find_digit(cell: usize, options: &[u64], owners: &mut [usize; MAX_SIZE], visited: &mut u64) -> bool {
+    for digit in bits(options[cell] & !*visited) {
+        *visited |= 1 << digit;
+        if owners[digit] == usize::MAX || find_digit(owners[digit], options, owners, visited) {
+            owners[digit] = cell;
+            return true;
+        }
+    }
+    false
+}
+
+impl Board {
+    /// For each cell in row-major order, the number of the killer cage it's in (if any),
+    /// and the cage's sum if it's the cage's first cell, where the sum is written when the board is drawn.
+    pub(crate) fn cage_cells(&self) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+        let geometry = self.geometry();
+        let mut cage_of = vec![None; geometry.num_cells()];
+        let mut sums = vec![None; geometry.num_cells()];
+        let cages = self.constraints_of::<KillerCages>().flat_map(|killer| killer.cages.iter());
+        for (number, cage) in cages.enumerate() {
+            let cells: Vec<usize> = cage.cells.iter().filter_map(|&location| geometry.index(location)).collect();
+            for &cell in cells.iter() {
+                cage_of[cell] = Some(number);
+            }
+            if let Some(&first) = cells.iter().min() {
+                sums[first] = Some(cage.sum);
+            }
+        }
+        (cage_of, sums)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+
+    /// The cell's candidates, counting from 1.
+    fn candidates(board: &Board, r: usize, c: usize) -> Vec<usize> {
+        board.get(Location::Valid(r, c)).possibilities.iter().enumerate().filter(|(_, &possible)| possible).map(|(digit, _)| digit + 1).collect()
+    }
+
+    #[test]
+    fn parse_and_to_text_round_trip() {
+        let cages = KillerCages::parse("AAB.\nCABD\nCCDD\nEE..\n6 5 7 8 3\n").unwrap();
+        assert_eq!(cages.cages().len(), 5);
+        assert_eq!(cages.cages()[0], Cage::new(6, vec![Location::Valid(0, 0), Location::Valid(0, 1), Location::Valid(1, 1)]));
+        assert_eq!(cages.cage_of(Location::Valid(2, 2)).map(Cage::sum), Some(8));
+        assert_eq!(cages.cage_of(Location::Valid(3, 3)), None);
+        assert_eq!(KillerCages::parse(&cages.to_text(4)).unwrap(), cages);
+    }
+
+    #[test]
+    fn parse_checks_the_sums() {
+        assert_eq!(KillerCages::parse("AB\nAB\n3").unwrap_err(), SudokuError::WrongSumCount { expected: 2, found: 1 });
+    }
+
+    #[test]
+    fn propagation_keeps_digits_that_make_the_sum() {
+        let mut board = Board::new(3, 3);
+        let cages = KillerCages::parse("AA.......\nBBB......\n.........\nCC.......\n.........\n.........\n.........\n.........\n.........\n3 24 10").unwrap();
+        board.add_constraint(cages.clone());
+        assert_eq!(cages.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 0, 0), vec![1, 2]);
+        assert_eq!(candidates(&board, 1, 2), vec![7, 8, 9]);
+        assert_eq!(candidates(&board, 3, 0), vec![1, 2, 3, 4, 6, 7, 8, 9]);
+
+        board.set(Location::Valid(3, 0), Cell::new_single_digit(9, 2));
+        assert_eq!(cages.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 3, 1), vec![7]);
+    }
+
+    #[test]
+    fn digits_a_cage_needs_leave_the_rest_of_its_houses() {
+        let mut board = Board::new(3, 3);
+        let cages = KillerCages::new(vec![Cage::new(3, vec![Location::Valid(0, 0), Location::Valid(0, 1)])]);
+        board.add_constraint(cages.clone());
+        assert_eq!(cages.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 0, 5), vec![3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(candidates(&board, 2, 2), vec![3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(candidates(&board, 5, 0), (1..=9).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn innies_add_up_to_the_rest_of_the_house() {
+        //The last two cells of the first row add up to 45 - 25 - 12 = 8.
+        let mut board = Board::new(3, 3);
+        let cages = KillerCages::parse("AAAAABB..\n.........\n.........\n.........\n.........\n.........\n.........\n.........\n.........\n25 12").unwrap();
+        board.add_constraint(cages.clone());
+        assert_eq!(cages.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 0, 7), vec![1, 2, 3, 5, 6, 7]);
+    }
+
+    #[test]
+    fn outies_are_what_the_cages_add_beyond_the_house() {
+        //The cages covering the first row add up to 20 + 30 = 50, so the cell sticking out of it is 5.
+        let mut board = Board::new(3, 3);
+        let cages = KillerCages::parse("AAAABBBBB\n...A.....\n.........\n.........\n.........\n.........\n.........\n.........\n.........\n20 30").unwrap();
+        board.add_constraint(cages.clone());
+        assert_eq!(cages.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 1, 3), vec![5]);
+    }
+
+    #[test]
+    fn too_many_fillings_to_list_allow_every_candidate() {
+        let candidates = vec![u64::MAX; 30];
+        let unlisted = fillings(&candidates, 1000, 64).unwrap();
+        assert_eq!(unlisted.allowed, candidates);
+        assert_eq!(unlisted.required, 0);
+        assert!(fillings(&[0b11, 0b11], 3, 9).is_some());
+        assert!(fillings(&[0b11, 0b11], 4, 9).is_none());
+    }
+
+    #[test]
+    fn try_to_text_writes_the_cages() {
+        let cages = KillerCages::parse("AB\nAB\n3 7").unwrap();
+        assert_eq!(cages.try_to_text(2), Ok("AB\nAB\n3 7\n".to_string()));
+    }
+
+    #[test]
+    fn wrong_sum_conflicts() {
+        let mut board = Board::new(3, 3);
+        let cages = KillerCages::new(vec![Cage::new(5, vec![Location::Valid(0, 0), Location::Valid(0, 1)])]);
+        board.add_constraint(cages.clone());
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(9, 0));
+        board.set(Location::Valid(0, 1), Cell::new_single_digit(9, 2));
+        assert!(cages.has_contradiction(&board));
+        assert_eq!(cages.conflicting_cells(&board), vec![Location::Valid(0, 0), Location::Valid(0, 1)]);
+    }
+}
@@ -0,0 +1,366 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    board::Board,
+    cell::Cell,
+    error::SudokuError,
+    killer::{Cage, KillerCages},
+    location::Location,
+    provenance::Provenance,
+    puzzle_generator::generate_solution,
+    rating::{Difficulty, Rating}
+};
+
+/// How many cage layouts to try before giving up on hitting the requested difficulty exactly.
+const MAX_DIFFICULTY_ATTEMPTS: usize = 20;
+
+/// How many random ways of cutting a cage in two to try before cutting off a single cell instead.
+const MAX_SPLIT_ATTEMPTS: usize = 20;
+
+/// How many boards to search through when checking whether a layout has one solution, before treating it as having more.
+/// A few layouts take a very long search to settle, and there are plenty of others to try instead.
+const MAX_SEARCH_BOARDS: usize = 500;
+
+/// Creates Killer Sudoku puzzles with exactly one solution: cages over a random solution grid,
+/// with no givens unless some are asked for.
+pub struct KillerGenerator {
+    rng: StdRng,
+    min_cage_size: usize,
+    max_cage_size: usize,
+    givens: usize,
+    difficulty: Option<Difficulty>,
+}
+
+impl Default for KillerGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KillerGenerator {
+    /// Creates a generator with a random seed.
+    pub fn new() -> Self {
+        Self::from_rng(StdRng::from_entropy())
+    }
+
+    /// Creates a generator that makes the same cages (and givens) every time it's given the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: StdRng) -> Self {
+        Self {
+            rng,
+            min_cage_size: 2,
+            max_cage_size: 5,
+            givens: 0,
+            difficulty: None,
+        }
+    }
+
+    /// Sets how many cells cages have, 2 to 5 by default.
+    /// Cages can end up smaller than `min` where the cells left over don't fit anywhere,
+    /// or where a cage has to be cut down for the puzzle to have one solution.
+    pub fn cage_sizes(mut self, min: usize, max: usize) -> Self {
+        self.min_cage_size = min.max(1);
+        self.max_cage_size = max.max(self.min_cage_size);
+        self
+    }
+
+    /// Gives the digits of this many random cells, none by default.
+    pub fn givens(mut self, givens: usize) -> Self {
+        self.givens = givens;
+        self
+    }
+
+    /// Only merges cages while generated puzzles stay at or below the given difficulty,
+    /// retrying with new layouts until one lands on it exactly.
+    pub fn difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = Some(difficulty);
+        self
+    }
+
+    /// Generates a killer puzzle with exactly one solution: a board with a `KillerCages` constraint and any givens,
+    /// along with how hard it is.
+    /// Panics if the box shape isn't supported; see `try_generate_puzzle`.
+    pub fn generate_puzzle(&mut self, rows_per_box: usize, cols_per_box: usize) -> (Board, Rating) {
+        match self.try_generate_puzzle(rows_per_box, cols_per_box) {
+            Ok(generated) => generated,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Same as `generate_puzzle`, but returns an error if the box shape isn't supported.
+    pub fn try_generate_puzzle(&mut self, rows_per_box: usize, cols_per_box: usize) -> Result<(Board, Rating), SudokuError> {
+        let blank = Board::try_new(rows_per_box, cols_per_box)?;
+        let Some(target) = self.difficulty else {
+            return Ok(self.generate_layout(&blank));
+        };
+
+        //Keep the attempt whose difficulty is closest to the target.
+        let mut best: Option<((Board, Rating), usize)> = None;
+        for _ in 0..MAX_DIFFICULTY_ATTEMPTS {
+            let generated = self.generate_layout(&blank);
+            let distance = (generated.1.difficulty as usize).abs_diff(target as usize);
+            if distance == 0 {
+                return Ok(generated);
+            }
+            if best.as_ref().is_none_or(|(_, best_distance)| distance < *best_distance) {
+                best = Some((generated, distance));
+            }
+        }
+
+        best.map(|(generated, _)| generated).ok_or(SudokuError::NoPuzzleGenerated)
+    }
+
+    /// Makes one puzzle and rates it: cuts a random solution into cages, splits cages until there's one solution,
+    /// then merges neighboring cages as long as there still is.
+    fn generate_layout(&mut self, blank: &Board) -> (Board, Rating) {
+        let size = blank.cells_per_set();
+        let (min, max) = (self.min_cage_size.min(size), self.max_cage_size.min(size));
+        let solution = generate_solution(&mut self.rng, blank);
+        let digits: Vec<usize> = (0..size * size).map(|cell| solution.get(location_of(cell, size)).get_single_index().unwrap_or(0)).collect();
+
+        let mut puzzle = blank.clone();
+        let mut cells: Vec<usize> = (0..size * size).collect();
+        cells.shuffle(&mut self.rng);
+        for &cell in cells.iter().take(self.givens) {
+            puzzle.set(location_of(cell, size), Cell::new_single_digit(size, digits[cell]).with_provenance(Provenance::Given));
+        }
+
+        let mut cages = self.random_cages(&digits, size, min, max);
+        //Split a cage where another solution differs from this one, until there's no other solution.
+        //If the search gives up before telling, any cage can be split.
+        loop {
+            let board = with_cages(&puzzle, &cages, &digits, size);
+            let differing: Vec<usize> = match board.solutions_within(2, MAX_SEARCH_BOARDS) {
+                Some(solutions) => {
+                    let Some(other) = solutions.into_iter().find(|found| found.to_line() != solution.to_line()) else {
+                        break;
+                    };
+                    (0..size * size).filter(|&cell| other.get(location_of(cell, size)).get_single_index() != Some(digits[cell])).collect()
+                }
+                None => (0..size * size).collect(),
+            };
+            let splittable: Vec<usize> = (0..cages.len()).filter(|&index| cages[index].len() > 1 && cages[index].iter().any(|cell| differing.contains(cell))).collect();
+            let Some(&index) = splittable.choose(&mut self.rng) else {
+                break;
+            };
+            let (part, rest) = self.split(&cages[index], size);
+            cages[index] = part;
+            cages.push(rest);
+        }
+
+        //Merge neighboring cages while the puzzle keeps one solution (and doesn't get too hard).
+        let mut pairs = neighboring_cages(&cages, size);
+        pairs.shuffle(&mut self.rng);
+        for (a, b) in pairs {
+            if cages[a].is_empty() || cages[b].is_empty() || cages[a].len() + cages[b].len() > max {
+                continue;
+            }
+            if cages[a].iter().any(|&cell| cages[b].iter().any(|&other| digits[cell] == digits[other])) {
+                continue;
+            }
+            let mut merged = cages.clone();
+            let moved = std::mem::take(&mut merged[b]);
+            merged[a].extend(moved);
+            let board = with_cages(&puzzle, &merged, &digits, size);
+            if board.solutions_within(2, MAX_SEARCH_BOARDS).is_none_or(|solutions| solutions.len() != 1) {
+                continue;
+            }
+            if let Some(target) = self.difficulty {
+                if board.rate().is_some_and(|rating| rating.difficulty > target) {
+                    continue;
+                }
+            }
+            cages = merged;
+        }
+
+        cages.retain(|cage| !cage.is_empty());
+        puzzle = with_cages(&puzzle, &cages, &digits, size);
+        puzzle.mark_givens();
+        //Every layout kept has one solution, so it always has a rating.
+        let rating = puzzle.rate().unwrap_or(Rating { difficulty: Difficulty::Expert, guesses: 0 });
+        (puzzle, rating)
+    }
+
+    /// Cuts the grid into connected cages of `min` to `max` cells, with no digit twice in a cage.
+    fn random_cages(&mut self, digits: &[usize], size: usize, min: usize, max: usize) -> Vec<Vec<usize>> {
+        let mut cage_of = vec![usize::MAX; size * size];
+        let mut cages: Vec<Vec<usize>> = Vec::new();
+        let mut order: Vec<usize> = (0..size * size).collect();
+        order.shuffle(&mut self.rng);
+        for start in order {
+            if cage_of[start] != usize::MAX {
+                continue;
+            }
+            let target = self.rng.gen_range(min..=max);
+            let mut cage = vec![start];
+            cage_of[start] = cages.len();
+            while cage.len() < target {
+                let options: Vec<usize> = cage
+                    .iter()
+                    .flat_map(|&cell| neighbors(cell, size))
+                    .filter(|&next| cage_of[next] == usize::MAX && cage.iter().all(|&cell| digits[cell] != digits[next]))
+                    .collect();
+                let Some(&next) = options.choose(&mut self.rng) else {
+                    break;
+                };
+                cage_of[next] = cages.len();
+                cage.push(next);
+            }
+            cages.push(cage);
+        }
+
+        //Cages left too small join a neighbor with room for them, if they can.
+        for index in 0..cages.len() {
+            if cages[index].is_empty() || cages[index].len() >= min {
+                continue;
+            }
+            let mut options: Vec<usize> = cages[index]
+                .iter()
+                .flat_map(|&cell| neighbors(cell, size))
+                .map(|cell| cage_of[cell])
+                .filter(|&other| other != index && cages[other].len() + cages[index].len() <= max)
+                .filter(|&other| cages[index].iter().all(|&cell| cages[other].iter().all(|&next| digits[cell] != digits[next])))
+                .collect();
+            options.dedup();
+            if let Some(&other) = options.choose(&mut self.rng) {
+                let moved = std::mem::take(&mut cages[index]);
+                for &cell in moved.iter() {
+                    cage_of[cell] = other;
+                }
+                cages[other].extend(moved);
+            }
+        }
+        cages.retain(|cage| !cage.is_empty());
+        cages
+    }
+
+    /// Cuts a cage into two connected pieces, about half and half if possible.
+    fn split(&mut self, cage: &[usize], size: usize) -> (Vec<usize>, Vec<usize>) {
+        for _ in 0..MAX_SPLIT_ATTEMPTS {
+            let Some(&start) = cage.choose(&mut self.rng) else { break };
+            let mut part = vec![start];
+            while part.len() < cage.len() / 2 {
+                let options: Vec<usize> = part.iter().flat_map(|&cell| neighbors(cell, size)).filter(|cell| cage.contains(cell) && !part.contains(cell)).collect();
+                let Some(&next) = options.choose(&mut self.rng) else { break };
+                part.push(next);
+            }
+            let rest: Vec<usize> = cage.iter().copied().filter(|cell| !part.contains(cell)).collect();
+            if !rest.is_empty() && is_connected(&rest, size) {
+                return (part, rest);
+            }
+        }
+        //Some cell can always be cut off alone, e.g. the last one reached by walking through the cage.
+        let mut reached = vec![cage[0]];
+        let mut next = 0;
+        while let Some(&cell) = reached.get(next) {
+            reached.extend(neighbors(cell, size).filter(|neighbor| cage.contains(neighbor) && !reached.contains(neighbor)).collect::<Vec<usize>>());
+            next += 1;
+        }
+        let last = reached[reached.len() - 1];
+        (vec![last], cage.iter().copied().filter(|&cell| cell != last).collect())
+    }
+}
+
+fn location_of(cell: usize, size: usize) -> Location {
+    Location::Valid(cell / size, cell % size)
+}
+
+/// The cells sharing a side with the cell.
+fn neighbors(cell: usize, size: usize) -> impl Iterator<Item = usize> {
+    let (r, c) = (cell / size, cell % size);
+    [(r.wrapping_sub(1), c), (r + 1, c), (r, c.wrapping_sub(1)), (r, c + 1)]
+        .into_iter()
+        .filter(move |&(r, c)| r < size && c < size)
+        .map(move |(r, c)| r * size + c)
+}
+
+fn is_connected(cells: &[usize], size: usize) -> bool {
+    let mut reached = vec![cells[0]];
+    let mut next = 0;
+    while let Some(&cell) = reached.get(next) {
+        reached.extend(neighbors(cell, size).filter(|neighbor| cells.contains(neighbor) && !reached.contains(neighbor)).collect::<Vec<usize>>());
+        next += 1;
+    }
+    reached.len() == cells.len()
+}
+
+/// Every pair of different cages with cells sharing a side, each pair once.
+fn neighboring_cages(cages: &[Vec<usize>], size: usize) -> Vec<(usize, usize)> {
+    let mut cage_of = vec![0; size * size];
+    for (index, cage) in cages.iter().enumerate() {
+        for &cell in cage.iter() {
+            cage_of[cell] = index;
+        }
+    }
+    let mut pairs = Vec::new();
+    for cell in 0..size * size {
+        for neighbor in neighbors(cell, size) {
+            let (a, b) = (cage_of[cell], cage_of[neighbor]);
+            if a < b && !pairs.contains(&(a, b)) {
+                pairs.push((a, b));
+            }
+        }
+    }
+    pairs
+}
+
+/// The puzzle with the given cages, summed over the solution's digits.
+fn with_cages(puzzle: &Board, cages: &[Vec<usize>], digits: &[usize], size: usize) -> Board {
+    let cages = cages
+        .iter()
+        .filter(|cage| !cage.is_empty())
+        .map(|cage| {
+            let mut cells = cage.clone();
+            cells.sort_unstable();
+            Cage::new(cells.iter().map(|&cell| digits[cell] + 1).sum(), cells.into_iter().map(|cell| location_of(cell, size)).collect())
+        })
+        .collect();
+    let mut board = puzzle.clone();
+    board.add_constraint(KillerCages::new(cages));
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_a_puzzle_with_one_solution() {
+        let (puzzle, rating) = KillerGenerator::with_seed(1).generate_puzzle(2, 3);
+        let cages = puzzle.constraint::<KillerCages>().unwrap();
+        assert!(cages.cages().iter().all(|cage| cage.cells().len() <= 5));
+        assert_eq!(puzzle.to_line(), ".".repeat(36));
+        assert_eq!(puzzle.count_solutions(2), 1);
+        assert_eq!(puzzle.rate(), Some(rating));
+    }
+
+    #[test]
+    fn generates_9x9_puzzles_with_the_default_cage_sizes() {
+        //This seed once took minutes, settling layouts that needed a very long search.
+        let (puzzle, rating) = KillerGenerator::with_seed(5).generate_puzzle(3, 3);
+        let cages = puzzle.constraint::<KillerCages>().unwrap();
+        assert!(cages.cages().iter().all(|cage| cage.cells().len() <= 5));
+        assert_eq!(puzzle.count_solutions(2), 1);
+        assert_eq!(puzzle.rate(), Some(rating));
+    }
+
+    #[test]
+    fn generates_givens_and_cage_sizes() {
+        let (puzzle, _) = KillerGenerator::with_seed(2).cage_sizes(2, 3).givens(4).generate_puzzle(2, 3);
+        let cages = puzzle.constraint::<KillerCages>().unwrap();
+        assert!(cages.cages().iter().all(|cage| cage.cells().len() <= 3));
+        assert_eq!(puzzle.to_line().chars().filter(|&c| c != '.').count(), 4);
+        assert_eq!(puzzle.count_solutions(2), 1);
+    }
+
+    #[test]
+    fn same_seed_same_puzzle() {
+        let (first, _) = KillerGenerator::with_seed(3).generate_puzzle(2, 3);
+        let (second, _) = KillerGenerator::with_seed(3).generate_puzzle(2, 3);
+        assert_eq!(first.to_line(), second.to_line());
+        assert_eq!(first.constraint::<KillerCages>(), second.constraint::<KillerCages>());
+    }
+}
@@ -0,0 +1,357 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    board::Board,
+    constraint::Constraint,
+    error::SudokuError,
+    killer::{bits, digit_bits},
+    location::{cell_name, parse_cell_names, Location}
+};
+
+/// The line of `KropkiDots::to_text` that turns on `KropkiDots::all_dots_given`.
+const ALL_DOTS_GIVEN: &str = "all dots given";
+
+/// What a Kropki dot between two cells says about their digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DotColor {
+    /// The digits are consecutive, e.g. 4 and 5.
+    White,
+    /// One digit is double the other, e.g. 3 and 6.
+    Black,
+}
+
+impl DotColor {
+    pub const ALL: [DotColor; 2] = [DotColor::White, DotColor::Black];
+
+    /// Lowercase name, as accepted by `from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            DotColor::White => "white",
+            DotColor::Black => "black",
+        }
+    }
+
+    /// Returns true iff two cells with these digit indices can have a dot of this color between them.
+    pub fn allows(self, a: usize, b: usize) -> bool {
+        let (a, b) = (a + 1, b + 1);
+        match self {
+            DotColor::White => a.abs_diff(b) == 1,
+            DotColor::Black => a == 2 * b || b == 2 * a,
+        }
+    }
+
+    /// The digits (as a bit per digit) the cell on the other side of a dot of this color can have, given this cell's digit.
+    fn partners(self, digit: usize, size: usize) -> u64 {
+        (0..size).filter(|&other| self.allows(digit, other)).fold(0, |partners, other| partners | 1 << other)
+    }
+}
+
+impl Display for DotColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for DotColor {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|color| color.name().eq_ignore_ascii_case(input))
+            .ok_or_else(|| format!("unknown dot color '{input}' (expected white or black)"))
+    }
+}
+
+/// A dot on the line between two cells sharing a side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Dot {
+    color: DotColor,
+    cells: (Location, Location),
+}
+
+impl Dot {
+    /// Panics if the cells don't share a side; see `try_new`.
+    pub fn new(color: DotColor, a: Location, b: Location) -> Self {
+        match Self::try_new(color, a, b) {
+            Ok(dot) => dot,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Returns an error if the cells don't share a side. `Board::try_add_constraint` checks they're on the board.
+    pub fn try_new(color: DotColor, a: Location, b: Location) -> Result<Self, SudokuError> {
+        match (a, b) {
+            (Location::Valid(r1, c1), Location::Valid(r2, c2)) if r1.abs_diff(r2) + c1.abs_diff(c2) == 1 => Ok(Self { color, cells: (a, b) }),
+            _ => Err(SudokuError::DotNotBetweenNeighbors(a, b)),
+        }
+    }
+
+    pub fn color(&self) -> DotColor {
+        self.color
+    }
+
+    pub fn cells(&self) -> (Location, Location) {
+        self.cells
+    }
+}
+
+/// The dots of a Kropki puzzle: white dots between consecutive digits and black dots between digits where one is double the other.
+/// Digits count as 1 up to the number of digits, whatever symbols draw them.
+/// With `all_dots_given`, neighbors with no dot between them also can't be consecutive or double one another.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KropkiDots {
+    dots: Vec<Dot>,
+    all_dots_given: bool,
+}
+
+impl KropkiDots {
+    pub fn new(dots: Vec<Dot>) -> Self {
+        Self { dots, all_dots_given: false }
+    }
+
+    /// Turns the negative rule on or off: every pair of neighbors that could have a dot has one.
+    pub fn all_dots_given(mut self, all_dots_given: bool) -> Self {
+        self.all_dots_given = all_dots_given;
+        self
+    }
+
+    /// Reads dots written a line each, as the color then the two cells, with a line saying `all dots given`
+    /// for the negative rule, like
+    ///
+    /// ```text
+    /// white r1c1 r1c2
+    /// black r4c5, r5c5
+    /// all dots given
+    /// ```
+    ///
+    /// Rows and cols count from 1, and cells are separated by whitespace or commas.
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(input: &str) -> Result<Self, SudokuError> {
+        let mut dots = Vec::new();
+        let mut all_dots_given = false;
+        for (line, text) in input.lines().enumerate() {
+            let trimmed = text.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if trimmed.split_whitespace().collect::<Vec<&str>>().join(" ").eq_ignore_ascii_case(ALL_DOTS_GIVEN) {
+                all_dots_given = true;
+                continue;
+            }
+            let indent = text.len() - text.trim_start().len();
+            let word = trimmed.split_whitespace().next().unwrap_or_default();
+            let color: DotColor = word.parse().map_err(|_| SudokuError::UnknownDot { line: line + 1, input: word.to_string() })?;
+            let rest = &text[indent + word.len()..];
+            let cells = parse_cell_names(rest, line + 1, text[..indent + word.len()].chars().count() + 1)?;
+            match cells[..] {
+                [a, b] => dots.push(Dot::try_new(color, a, b)?),
+                _ => return Err(SudokuError::UnknownDot { line: line + 1, input: trimmed.to_string() }),
+            }
+        }
+        Ok(Self::new(dots).all_dots_given(all_dots_given))
+    }
+
+    /// Writes the dots in the form `parse` reads.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for dot in self.dots.iter() {
+            text += &format!("{} {} {}\n", dot.color, cell_name(dot.cells.0), cell_name(dot.cells.1));
+        }
+        if self.all_dots_given {
+            text += ALL_DOTS_GIVEN;
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn dots(&self) -> &[Dot] {
+        &self.dots
+    }
+
+    /// Returns whether the negative rule is on.
+    pub fn has_all_dots_given(&self) -> bool {
+        self.all_dots_given
+    }
+
+    /// Every pair of neighbors on a board of the given size with no dot between them, for the negative rule.
+    fn undotted_pairs(&self, size: usize) -> Vec<(Location, Location)> {
+        //Whether there's a dot right of each cell, then below each cell.
+        let mut dotted = vec![false; 2 * size * size];
+        for dot in self.dots.iter() {
+            if let (Location::Valid(r1, c1), Location::Valid(r2, c2)) = dot.cells {
+                let (r, c) = (r1.min(r2), c1.min(c2));
+                let index = if r1 == r2 { r * size + c } else { size * size + r * size + c };
+                if let Some(dotted) = dotted.get_mut(index) {
+                    *dotted = true;
+                }
+            }
+        }
+
+        let mut pairs = Vec::new();
+        for r in 0..size {
+            for c in 0..size {
+                if c + 1 < size && !dotted[r * size + c] {
+                    pairs.push((Location::Valid(r, c), Location::Valid(r, c + 1)));
+                }
+                if r + 1 < size && !dotted[size * size + r * size + c] {
+                    pairs.push((Location::Valid(r, c), Location::Valid(r + 1, c)));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+impl Constraint for KropkiDots {
+    fn name(&self) -> &str {
+        "kropki dots"
+    }
+
+    fn cells(&self, _board: &Board) -> Vec<Location> {
+        self.dots.iter().flat_map(|dot| [dot.cells.0, dot.cells.1]).collect()
+    }
+
+    fn propagate(&self, board: &mut Board) -> Option<bool> {
+        let size = board.cells_per_set();
+        //Keeps the digits of the first cell that have a partner the rule allows among the second cell's candidates.
+        let mut found_something = false;
+        let mut keep_supported = |board: &mut Board, cell: Location, other: Location, partners: &dyn Fn(usize) -> u64| -> Option<()> {
+            let others = digit_bits(&board.get(other).possibilities);
+            let digits = digit_bits(&board.get(cell).possibilities);
+            let unsupported: Vec<usize> = bits(digits).filter(|&digit| partners(digit) & others == 0).collect();
+            if unsupported.len() == digits.count_ones() as usize {
+                return None;
+            }
+            let possibilities = &mut board.get_mut(cell).possibilities;
+            for digit in unsupported {
+                possibilities[digit] = false;
+                found_something = true;
+            }
+            Some(())
+        };
+
+        for dot in self.dots.iter() {
+            let partners = |digit: usize| dot.color.partners(digit, size);
+            let (a, b) = dot.cells;
+            keep_supported(board, a, b, &partners)?;
+            keep_supported(board, b, a, &partners)?;
+        }
+        if self.all_dots_given {
+            let everything = u64::MAX >> (64 - size);
+            let unrelated = |digit: usize| everything & !(DotColor::White.partners(digit, size) | DotColor::Black.partners(digit, size));
+            for (a, b) in self.undotted_pairs(size) {
+                keep_supported(board, a, b, &unrelated)?;
+                keep_supported(board, b, a, &unrelated)?;
+            }
+        }
+        Some(found_something)
+    }
+
+    fn has_contradiction(&self, board: &Board) -> bool {
+        let digits = |(a, b): (Location, Location)| Some((board.get(a).get_single_index()?, board.get(b).get_single_index()?));
+        let broken_dot = self.dots.iter().any(|dot| digits(dot.cells).is_some_and(|(a, b)| !dot.color.allows(a, b)));
+        let missing_dot = || {
+            self.undotted_pairs(board.cells_per_set())
+                .into_iter()
+                .any(|pair| digits(pair).is_some_and(|(a, b)| DotColor::ALL.iter().any(|color| color.allows(a, b))))
+        };
+        broken_dot || (self.all_dots_given && missing_dot())
+    }
+
+    fn conflicting_cells(&self, board: &Board) -> Vec<Location> {
+        let digits = |(a, b): (Location, Location)| Some((board.get(a).get_single_index()?, board.get(b).get_single_index()?));
+        let broken_dots = self.dots.iter().filter(|dot| digits(dot.cells).is_some_and(|(a, b)| !dot.color.allows(a, b))).map(|dot| dot.cells);
+        let missing_dots = if self.all_dots_given { self.undotted_pairs(board.cells_per_set()) } else { Vec::new() }
+            .into_iter()
+            .filter(|&pair| digits(pair).is_some_and(|(a, b)| DotColor::ALL.iter().any(|color| color.allows(a, b))));
+        broken_dots.chain(missing_dots).flat_map(|(a, b)| [a, b]).collect()
+    }
+}
+
+impl Board {
+    /// For each cell in row-major order, the color of the Kropki dot between it and the cell to its right,
+    /// then the same for the cell below it.
+    pub(crate) fn kropki_dots(&self) -> (Vec<Option<DotColor>>, Vec<Option<DotColor>>) {
+        let geometry = self.geometry();
+        let (mut right, mut below) = (vec![None; geometry.num_cells()], vec![None; geometry.num_cells()]);
+        for dot in self.constraints_of::<KropkiDots>().flat_map(|dots| dots.dots.iter()) {
+            let (Location::Valid(r1, c1), Location::Valid(r2, c2)) = dot.cells else { continue };
+            let Some(cell) = geometry.index(Location::Valid(r1.min(r2), c1.min(c2))) else { continue };
+            if r1 == r2 {
+                right[cell] = Some(dot.color);
+            }
+            else {
+                below[cell] = Some(dot.color);
+            }
+        }
+        (right, below)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+
+    /// The cell's candidates, counting from 1.
+    fn candidates(board: &Board, r: usize, c: usize) -> Vec<usize> {
+        board.get(Location::Valid(r, c)).possibilities.iter().enumerate().filter(|(_, &possible)| possible).map(|(digit, _)| digit + 1).collect()
+    }
+
+    #[test]
+    fn colors_allow_consecutive_and_double_digits() {
+        assert!(DotColor::White.allows(2, 3));
+        assert!(!DotColor::White.allows(2, 4));
+        assert!(DotColor::Black.allows(2, 5));
+        assert!(!DotColor::Black.allows(2, 4));
+        for color in DotColor::ALL {
+            assert_eq!(color.name().parse::<DotColor>(), Ok(color));
+        }
+    }
+
+    #[test]
+    fn parse_and_to_text_round_trip() {
+        let dots = KropkiDots::parse("white r1c1 r1c2\n# a comment\nblack r4c5, r5c5\nall dots given\n").unwrap();
+        assert!(dots.has_all_dots_given());
+        assert_eq!(dots.dots()[1], Dot::new(DotColor::Black, Location::Valid(3, 4), Location::Valid(4, 4)));
+        assert_eq!(dots.to_text(), "white r1c1 r1c2\nblack r4c5 r5c5\nall dots given\n");
+        assert_eq!(KropkiDots::parse(&dots.to_text()).unwrap(), dots);
+    }
+
+    #[test]
+    fn parse_reports_bad_lines() {
+        assert_eq!(KropkiDots::parse("white r1c1 r1c2\ngrey r2c1 r2c2").unwrap_err(), SudokuError::UnknownDot { line: 2, input: "grey".to_string() });
+        assert_eq!(KropkiDots::parse("black r1c1 r2c2").unwrap_err(), SudokuError::DotNotBetweenNeighbors(Location::Valid(0, 0), Location::Valid(1, 1)));
+    }
+
+    #[test]
+    fn propagation_keeps_digits_with_a_partner() {
+        let mut board = Board::new(3, 3);
+        let dots = KropkiDots::parse("black r1c1 r1c2\nwhite r2c1 r2c2").unwrap();
+        board.add_constraint(dots.clone());
+        assert_eq!(dots.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 0, 0), vec![1, 2, 3, 4, 6, 8]);
+        assert_eq!(candidates(&board, 1, 0).len(), 9);
+
+        board.set(Location::Valid(1, 0), Cell::new_single_digit(9, 4));
+        assert_eq!(dots.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 1, 1), vec![4, 6]);
+    }
+
+    #[test]
+    fn negative_rule_removes_related_digits_without_a_dot() {
+        let mut board = Board::new(2, 2);
+        let dots = KropkiDots::new(Vec::new()).all_dots_given(true);
+        board.add_constraint(dots.clone());
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(4, 0));
+        assert_eq!(dots.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 0, 1), vec![1, 3, 4]);
+        assert_eq!(candidates(&board, 1, 0), vec![1, 3, 4]);
+
+        board.set(Location::Valid(0, 1), Cell::new_single_digit(4, 1));
+        assert!(dots.has_contradiction(&board));
+        assert_eq!(dots.conflicting_cells(&board), vec![Location::Valid(0, 0), Location::Valid(0, 1)]);
+    }
+}
@@ -0,0 +1,181 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    board::Board,
+    cell::Cell,
+    error::SudokuError,
+    kropki::{Dot, DotColor, KropkiDots},
+    location::Location,
+    provenance::Provenance,
+    puzzle_generator::generate_solution
+};
+
+/// Creates Kropki puzzles with exactly one solution: dots over a random solution grid, and as few givens as it needs.
+/// Without the negative rule it also leaves out dots, as long as the puzzle still solves without guessing.
+pub struct KropkiGenerator {
+    rng: StdRng,
+    all_dots_given: bool,
+}
+
+impl Default for KropkiGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KropkiGenerator {
+    /// Creates a generator with a random seed.
+    pub fn new() -> Self {
+        Self::from_rng(StdRng::from_entropy())
+    }
+
+    /// Creates a generator that makes the same dots and givens every time it's given the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: StdRng) -> Self {
+        Self { rng, all_dots_given: false }
+    }
+
+    /// Shows every dot the solution has and turns on the negative rule, instead of leaving out whichever dots it can.
+    pub fn all_dots_given(mut self, all_dots_given: bool) -> Self {
+        self.all_dots_given = all_dots_given;
+        self
+    }
+
+    /// Generates a Kropki puzzle with exactly one solution: a board with a `KropkiDots` constraint and givens.
+    /// `Board::rate` tells how hard it is.
+    /// Panics if the box shape isn't supported; see `try_generate_puzzle`.
+    pub fn generate_puzzle(&mut self, rows_per_box: usize, cols_per_box: usize) -> Board {
+        match self.try_generate_puzzle(rows_per_box, cols_per_box) {
+            Ok(board) => board,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Same as `generate_puzzle`, but returns an error if the box shape isn't supported.
+    pub fn try_generate_puzzle(&mut self, rows_per_box: usize, cols_per_box: usize) -> Result<Board, SudokuError> {
+        let blank = Board::try_new(rows_per_box, cols_per_box)?;
+        let size = blank.cells_per_set();
+        let solution = generate_solution(&mut self.rng, &blank);
+        let all_dots_given = self.all_dots_given;
+        let digit = |location: Location| solution.get(location).get_single_index().unwrap_or(0);
+
+        //Every pair of neighbors whose digits are related gets a dot. 1 and 2 are both, so they get either.
+        let mut dots = Vec::new();
+        for r in 0..size {
+            for c in 0..size {
+                for other in [Location::Valid(r, c + 1), Location::Valid(r + 1, c)] {
+                    let Location::Valid(r2, c2) = other else { continue };
+                    if r2 >= size || c2 >= size {
+                        continue;
+                    }
+                    let colors: Vec<DotColor> = DotColor::ALL.into_iter().filter(|color| color.allows(digit(Location::Valid(r, c)), digit(other))).collect();
+                    if let Some(&color) = colors.choose(&mut self.rng) {
+                        dots.push(Dot::new(color, Location::Valid(r, c), other));
+                    }
+                }
+            }
+        }
+
+        //Without the negative rule, leaving out dots quickly makes puzzles too slow to check for one solution by searching,
+        //so it stops where the puzzle would need guesses.
+        let is_pinned_down = |board: &Board| if all_dots_given { board.count_solutions(2) == 1 } else { solves_without_guessing(board) };
+
+        //Give the digit of a random cell that's not pinned down yet, until the whole solution is.
+        let mut givens: Vec<Location> = Vec::new();
+        loop {
+            let board = with_dots(&blank, &givens, &dots, &solution, all_dots_given);
+            let open: Vec<Location> = if all_dots_given {
+                let Some(other) = board.solutions(2).into_iter().find(|found| found.to_line() != solution.to_line()) else {
+                    break;
+                };
+                (0..size * size)
+                    .map(|cell| Location::Valid(cell / size, cell % size))
+                    .filter(|&location| other.get(location).get_single_index() != Some(digit(location)))
+                    .collect()
+            }
+            else {
+                let mut reduced = board.clone();
+                if reduced.fast_reduction_loop(true).is_some() && reduced.is_solved() {
+                    break;
+                }
+                (0..size * size).map(|cell| Location::Valid(cell / size, cell % size)).filter(|&location| !reduced.get(location).solved).collect()
+            };
+            let Some(&location) = open.choose(&mut self.rng) else {
+                break;
+            };
+            givens.push(location);
+        }
+
+        //Take away givens, then dots unless they all have to be shown, while the puzzle stays pinned down.
+        givens = self.remove_while(givens, |givens| is_pinned_down(&with_dots(&blank, givens, &dots, &solution, all_dots_given)));
+        if !all_dots_given {
+            dots = self.remove_while(dots, |dots| is_pinned_down(&with_dots(&blank, &givens, dots, &solution, all_dots_given)));
+        }
+        Ok(with_dots(&blank, &givens, &dots, &solution, all_dots_given))
+    }
+
+    /// Tries taking away each item in random order, keeping it out if the rest still pass the check.
+    /// Keeps the items' order.
+    fn remove_while<T: Clone>(&mut self, items: Vec<T>, check: impl Fn(&[T]) -> bool) -> Vec<T> {
+        let mut kept = vec![true; items.len()];
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        order.shuffle(&mut self.rng);
+        for index in order {
+            kept[index] = false;
+            let fewer: Vec<T> = items.iter().zip(kept.iter()).filter(|(_, &kept)| kept).map(|(item, _)| item.clone()).collect();
+            if !check(&fewer) {
+                kept[index] = true;
+            }
+        }
+        items.into_iter().zip(kept).filter(|(_, kept)| *kept).map(|(item, _)| item).collect()
+    }
+}
+
+/// The blank board with the solution's digits in the given cells, and the dots.
+fn with_dots(blank: &Board, givens: &[Location], dots: &[Dot], solution: &Board, all_dots_given: bool) -> Board {
+    let size = blank.cells_per_set();
+    let mut board = blank.clone();
+    for &location in givens {
+        let digit = solution.get(location).get_single_index().unwrap_or(0);
+        board.set(location, Cell::new_single_digit(size, digit).with_provenance(Provenance::Given));
+    }
+    board.add_constraint(KropkiDots::new(dots.to_vec()).all_dots_given(all_dots_given));
+    board.mark_givens();
+    board
+}
+
+/// Returns true iff singles and the constraints' own logic solve the board, without guessing.
+fn solves_without_guessing(board: &Board) -> bool {
+    let mut board = board.clone();
+    board.fast_reduction_loop(true).is_some() && board.is_solved() && !board.has_contradiction()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_a_puzzle_with_one_solution() {
+        let puzzle = KropkiGenerator::with_seed(1).generate_puzzle(3, 3);
+        assert!(puzzle.constraint::<KropkiDots>().is_some_and(|dots| !dots.has_all_dots_given()));
+        assert_eq!(puzzle.count_solutions(2), 1);
+    }
+
+    #[test]
+    fn generates_a_puzzle_with_all_dots_given() {
+        let puzzle = KropkiGenerator::with_seed(2).all_dots_given(true).generate_puzzle(2, 3);
+        assert!(puzzle.constraint::<KropkiDots>().is_some_and(KropkiDots::has_all_dots_given));
+        assert_eq!(puzzle.count_solutions(2), 1);
+    }
+
+    #[test]
+    fn same_seed_same_puzzle() {
+        let first = KropkiGenerator::with_seed(3).generate_puzzle(2, 3);
+        let second = KropkiGenerator::with_seed(3).generate_puzzle(2, 3);
+        assert_eq!(first.to_line(), second.to_line());
+        assert_eq!(first.constraint::<KropkiDots>(), second.constraint::<KropkiDots>());
+    }
+}
@@ -0,0 +1,119 @@
+//! A Sudoku solver and puzzle generator for any box shape up to `MAX_SIZE` cells per row, col, and box.
+//!
+//! Everything public is re-exported from the crate root, so the modules behind it can be
+//! reorganized without breaking anyone who depends on the crate.
+//!
+//! - Boards and cells: `Board`, `Cell`, `SudokuError`, `Provenance`, `Technique`, `Board::try_enter`, `Board::mark_givens`
+//! - Geometry: `Geometry`, `Location`, `cell_name`, `DigitSet`, `IndexIterator`, `DigitIterator`, `box_shape`, `MAX_SIZE`
+//! - Constraints: `Constraint`, `RowConstraint`, `ColConstraint`, `BoxConstraint`, `Board::constraints`, `Board::constraint`, `Board::constraints_of`, `Board::try_add_constraint`, `Board::try_add_shared_constraint`, `Board::remove_constraints_of`
+//! - Variants: `Variant`, `DiagonalConstraint`, `HyperConstraint`, `ExtraRegions`, `AntiKnightConstraint`, `AntiKingConstraint`
+//! - Killer: `KillerCages`, `Cage`, `KillerCages::parse`, `KillerCages::to_text`, `KillerCages::try_to_text`, `KillerGenerator`
+//! - Thermo: `Thermometers`, `Thermometer`, `Thermometers::parse`, `Thermometers::to_text`
+//! - Arrow sums: `Arrows`, `Arrow`, `Arrows::parse`, `Arrows::to_text`
+//! - Kropki: `KropkiDots`, `Dot`, `DotColor`, `KropkiDots::parse`, `KropkiDots::to_text`, `KropkiDots::all_dots_given`, `KropkiGenerator`
+//! - Jigsaw: `JigsawRegions`, `JigsawRegions::parse_map`, `Board::try_set_regions`, `PuzzleGenerator::regions` (`Board::parse_grid` reads irregular thick borders as regions too)
+//! - Solving: `Board::fast_solve`, `Board::solve`, `Board::count_solutions`, `Board::is_solved`, `Board::has_contradiction`
+//! - Rating: `Board::rate`, `Difficulty`, `Rating`
+//! - Generation: `PuzzleGenerator` (and `PuzzleGenerator::constraint` for variants), `Symmetry`
+//! - Rendering: `Display` (and `{:#}` for candidates), `Board::render`, `Board::render_solution`, `RenderOptions`, `Board::to_svg`, `SvgOptions`, `Booklet`, `PaperSize`
+//! - Parsing and symbols: `Board::parse` (and `FromStr`), `Board::parse_with_shape`, `Board::parse_with_symbols`, `Board::parse_grid`, `Board::parse_grid_with_symbols`, `Board::to_pencil_marks`, `Board::parse_pencil_marks`, `Board::parse_pencil_marks_with_symbols`, `Board::to_line`, `SymbolSet`, `Board::from_chars`, `Board::try_from_chars`, `Cell::try_new_single_char`, `digit_from_char`, `char_from_digit`, `DIGIT_CHARS`
+//! - Serialization (with the `serde` feature): `Serialize` and `Deserialize` for `Board`, `Cell`, `Location`, `DigitSet`, `Provenance`, `Technique`, `DotColor`, and `Difficulty`
+//!
+//! # JSON
+//!
+//! With the `serde` feature, a `Board` is written as
+//!
+//! ```text
+//! {
+//!   "rows_per_box": 3,
+//!   "cols_per_box": 3,
+//!   "symbols": "123456789",
+//!   "givens": "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+//!   "cells": [
+//!     { "candidates": "5", "solved": true, "provenance": "Given" },
+//!     { "candidates": "124", "solved": false, "provenance": "Unknown" },
+//!     ...
+//!   ],
+//!   "constraints": [
+//!     { "type": "rows" }, { "type": "cols" }, { "type": "boxes" },
+//!     { "type": "thermometers", "thermometers": ["r1c1 r1c2 r1c3"] },
+//!     { "type": "kropki", "dots": [{ "color": "White", "cells": "r5c5 r5c6" }], "all_dots_given": false }
+//!   ],
+//!   "metadata": { "size": 9, "given_count": 30, "solved_count": 30, "solved": false }
+//! }
+//! ```
+//!
+//! - `rows_per_box` and `cols_per_box` give the box shape.
+//! - `symbols` are the chars the board's digits are written with, in digit order (see `SymbolSet`).
+//! - `givens` has one symbol per clue and `.` per other cell, row by row, like `Board::to_line`.
+//! - `cells` has every cell row by row, with its candidates written as symbols, its solved flag, and its provenance,
+//!   which is `"Unknown"`, `"Given"`, `"Player"`, `{ "Technique": "NakedSingle" }`, or `{ "Guess": { "depth": 2 } }`.
+//!   Every cell needs at least one candidate, and a solved cell exactly one; a board with any other cell can't be read or written.
+//! - `constraints` has every constraint of the board, tagged with its `type`: `rows`, `cols`, `boxes`, `diagonals`, `hyper`,
+//!   `anti_knight`, `anti_king`, `extra_regions` (with `regions`), `jigsaw` (with the `map` `JigsawRegions::to_map` writes),
+//!   `killer` (with `cages`, each a `sum` and its `cells`), `thermometers`, `arrows` (each a `circle` and its `cells`),
+//!   or `kropki` (with `dots`, each a `color` and its two `cells`, and `all_dots_given`). Lists of cells are written as cell names,
+//!   like `"r1c1 r1c2"`. Writing a board with any other type of constraint is an error, rather than leaving it out.
+//! - `metadata` is worked out from the board when writing, and ignored when reading.
+//!
+//! Only the box shape is needed when reading: `symbols` defaults to `SymbolSet::digits`, `provenance` to `"Unknown"`,
+//! a board with neither `cells` nor `givens` is blank, and one without `constraints` has just rows, cols, and boxes.
+//! When there are `cells`, `givens` is ignored,
+//! so a puzzle can be sent as just `{ "rows_per_box": 3, "cols_per_box": 3, "givens": "53..7...." }`.
+//! `Cell`, `Location`, and `DigitSet` use serde's default (externally tagged) forms, e.g.
+//! `{ "Valid": [0, 4] }` for a `Location` and `{ "Box": [9, 4] }` for a `DigitSet`.
+
+mod arrow;
+mod board;
+mod booklet;
+mod boolean_operation;
+mod cell;
+mod constraint;
+mod digit_iterator;
+mod digit_set;
+mod error;
+mod geometry;
+mod index_iterator;
+mod killer;
+mod killer_generator;
+mod kropki;
+mod kropki_generator;
+mod location;
+mod parse;
+mod pencil_marks;
+mod provenance;
+mod puzzle_generator;
+mod rating;
+mod regions;
+mod render;
+#[cfg(feature = "serde")]
+mod serialize;
+mod svg;
+mod symbols;
+mod thermo;
+mod variant;
+
+pub use arrow::{Arrow, Arrows};
+pub use board::{box_shape, Board, SolveStats, MAX_SIZE};
+pub use booklet::{Booklet, BookletPuzzle, PaperSize};
+pub use cell::{char_from_digit, digit_from_char, Cell, DIGIT_CHARS};
+pub use constraint::{BoxConstraint, ColConstraint, Constraint, RowConstraint};
+pub use digit_iterator::DigitIterator;
+pub use digit_set::DigitSet;
+pub use error::SudokuError;
+pub use geometry::Geometry;
+pub use index_iterator::IndexIterator;
+pub use killer::{Cage, KillerCages};
+pub use killer_generator::KillerGenerator;
+pub use kropki::{Dot, DotColor, KropkiDots};
+pub use kropki_generator::KropkiGenerator;
+pub use location::{cell_name, Location};
+pub use provenance::{Provenance, Technique};
+pub use puzzle_generator::{PuzzleGenerator, Symmetry};
+pub use rating::{Difficulty, Rating, HARD_MAX_GUESSES};
+pub use regions::{ExtraRegions, HyperConstraint, JigsawRegions};
+pub use render::RenderOptions;
+pub use svg::SvgOptions;
+pub use symbols::SymbolSet;
+pub use thermo::{Thermometer, Thermometers};
+pub use variant::{AntiKingConstraint, AntiKnightConstraint, DiagonalConstraint, Variant};
@@ -1,10 +1,13 @@
 use std::fmt::Display;
 
-#[derive(Clone, Copy, PartialEq)]
+use crate::error::SudokuError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Location {
     Valid(usize, usize),
     Invalid,
-    Duplicate
+    Duplicate,
 }
 
 impl Display for Location {
@@ -21,4 +24,49 @@ impl Display for Location {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Names a cell the way players do, e.g. r1c5 for the fifth cell of the top row.
+pub fn cell_name(location: Location) -> String {
+    match location {
+        Location::Valid(r, c) => format!("r{}c{}", r + 1, c + 1),
+        other => other.to_string(),
+    }
+}
+
+/// Reads cells named like `cell_name` does, separated by whitespace or commas, from one line of input.
+/// `line` and `first_column` are where the text starts in the input (counting from 1), for errors.
+pub(crate) fn parse_cell_names(text: &str, line: usize, first_column: usize) -> Result<Vec<Location>, SudokuError> {
+    let mut cells = Vec::new();
+    let mut chars = text.chars().enumerate().map(|(column, c)| (column + first_column, c)).peekable();
+    while let Some(&(column, c)) = chars.peek() {
+        if c.is_whitespace() || c == ',' {
+            chars.next();
+            continue;
+        }
+        //A cell cut short at the end of the text is reported at its first char.
+        let start = SudokuError::UnexpectedChar { line, column, input: c };
+        let mut number = |prefix: char| {
+            match chars.next() {
+                Some((_, c)) if c.eq_ignore_ascii_case(&prefix) => {}
+                Some((column, c)) => return Err(SudokuError::UnexpectedChar { line, column, input: c }),
+                None => return Err(start.clone()),
+            }
+            let mut value: Option<usize> = None;
+            while let Some(&(_, c)) = chars.peek() {
+                let Some(digit) = c.to_digit(10) else { break };
+                value = Some(value.unwrap_or(0).saturating_mul(10).saturating_add(digit as usize));
+                chars.next();
+            }
+            match (value, chars.peek()) {
+                (Some(value), _) if value > 0 => Ok(value - 1),
+                (_, Some(&(column, c))) => Err(SudokuError::UnexpectedChar { line, column, input: c }),
+                (_, None) => Err(start.clone()),
+            }
+        };
+        let r = number('r')?;
+        let c = number('c')?;
+        cells.push(Location::Valid(r, c));
+    }
+    Ok(cells)
+}
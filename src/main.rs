@@ -1,343 +1,8 @@
-use std::time::Instant;
+use std::process::ExitCode;
 
-pub mod board;
-pub mod cell;
-pub mod digit_set;
-pub mod digit_iterator;
-pub mod index_iterator;
-pub mod boolean_operation;
-pub mod puzzle_generator;
-pub mod location;
+mod cli;
 
-#[derive(Clone, Copy)]
-pub enum PuzzleToSolve {
-   VeryHard,
-   Hard0,
-   Hard1,
-   Medium0,
-   Medium1,
-   Medium2,
-   Easy,
-   Impossible,
-   TopRow,
-   Blank
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    cli::run(&args)
 }
-
-fn main() {
-   const NUM_PUZZLES: u128 = 1000;
-   const ROWS_PER_BOX: usize = 3;
-   const COLS_PER_BOX: usize = 4;
-   let mut generator = puzzle_generator::PuzzleGenerator::new();
-   let mut fast_total = 0;
-   for puzzle_num in 0..NUM_PUZZLES {
-      let mut board = generator.generate_puzzle(ROWS_PER_BOX, COLS_PER_BOX);
-      println!("{board}");
-      if puzzle_num % (NUM_PUZZLES / 100) == 0 {
-         println!("Solved {puzzle_num} so far...");
-      }
-      let start = Instant::now();
-      board.fast_solve();
-      fast_total += start.elapsed().as_nanos();
-      println!("{board}");
-   }
-   let fast_average = fast_total / NUM_PUZZLES;
-   println!("Fast is {} nanoseconds on average.", format_large_number(fast_average));
-
-   // let mut slow_total = 0;
-   // for _ in 0..10 {
-   //    let start = Instant::now();
-   //    let mut board = board::Board::from_chars(&select_puzzle(sudoku_to_solve));
-   //    board.solve();
-   //    slow_total += start.elapsed().as_nanos();
-   // }
-   // let slow_average = slow_total / 10;
-   // println!("Slow is {slow_average} nanoseconds on average.");
-
-   // println!("Fast is {} times faster on average.", slow_average/fast_average);
-}
-
-fn format_large_number(input: u128) -> String {
-   let mut output = format!("{input}");
-   let len = output.len();
-   if len >= 3 {
-      for i in (1..len-2).rev().step_by(3) {
-         output.insert(i, ',');
-      }
-   }
-   output
-}
-
-#[allow(dead_code)]
-fn select_puzzle(sudoku_to_solve: PuzzleToSolve) -> [[char; 9]; 9] {
-   let initial = match sudoku_to_solve {
-      //Very hard sudoku (takes significantly longer than the others)
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-               │   │   ┃   │ 8 │   ┃   │ 7 │ 3
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 1 │ 4 ┃   │   │   ┃   │   │ 
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │ 
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-             7 │   │   ┃   │ 3 │   ┃   │   │ 
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 5 │   ┃   │   │   ┃ 4 │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃ 6 │   │   ┃   │ 2 │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │ 6 ┃ 4 │   │ 5 ┃ 2 │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃ 1 │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             3 │   │   ┃   │   │   ┃   │   │  
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::VeryHard => {
-         [['.','.','.','.','8','.','.','7','3'],['.','1','4','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['7','.','.','.','3','.','.','.','.'],['.','5','.','.','.','.','4','.','.'],['.','.','.','6','.','.','.','2','.'],['.','.','6','4','.','5','2','.','.'],['.','.','.','1','.','.','.','.','.'],['3','.','.','.','.','.','.','.','.']]
-      }
-
-
-      //Quite hard (for a human)
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-               │   │   ┃   │   │   ┃   │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 9 │   ┃   │ 1 │   ┃   │ 3 │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │ 6 ┃   │ 2 │   ┃ 7 │   │
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃ 3 │   │ 4 ┃   │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             2 │ 1 │   ┃   │   │   ┃   │ 9 │ 8
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │ 2 ┃ 5 │   │ 6 ┃ 4 │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 8 │   ┃   │   │   ┃   │ 1 │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::Hard0 => {
-         [['.','.','.','.','.','.','.','.','.'],['.','9','.','.','1','.','.','3','.'],['.','.','6','.','2','.','7','.','.'],['.','.','.','3','.','4','.','.','.'],['2','1','.','.','.','.','.','9','8'],['.','.','.','.','.','.','.','.','.'],['.','.','2','5','.','6','4','.','.'],['.','8','.','.','.','.','.','1','.'],['.','.','.','.','.','.','.','.','.']]
-      }
-
-
-      //Quite hard (for a human)
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-               │   │   ┃   │   │   ┃ 9 │   │ 1
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 5 │   ┃ 7 │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             2 │ 8 │   ┃   │   │   ┃   │   │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃ 5 │   │   ┃   │ 6 │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             1 │   │   ┃ 4 │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             3 │   │   ┃   │   │   ┃   │   │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │ 6 │   ┃   │   │   ┃   │ 5 │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │ 3 │ 2 ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │ 1 │   ┃ 7 │   │  
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::Hard1 => {
-         [['.','.','.','.','.','.','9','.','1'],['.','5','.','7','.','.','.','.','.'],['2','8','.','.','.','.','.','.','.'],['.','.','.','5','.','.','.','6','.'],['1','.','.','4','.','.','.','.','.'],['3','.','.','.','.','.','.','.','.'],['.','6','.','.','.','.','.','5','.'],['.','.','.','.','3','2','.','.','.'],['.','.','.','.','1','.','7','.','.']]
-      }
-
-
-      //Not that hard
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-               │   │   ┃ 4 │ 8 │   ┃ 6 │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃ 2 │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 7 │ 3 ┃   │ 6 │   ┃   │   │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │ 4 ┃   │   │ 1 ┃   │ 5 │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │ 9 ┃   │   │   ┃   │ 1 │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 3 │   ┃ 9 │   │   ┃ 8 │   │ 7
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │ 6 │   ┃   │ 1 │   ┃ 5 │ 3 │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃ 6 │ 5 │   ┃ 1 │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             9 │   │   ┃ 8 │ 3 │   ┃   │   │  
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::Medium0 => {
-         [['.','.','.','4','8','.','6','.','.'],['.','.','.','.','.','.','2','.','.'],['.','7','3','.','6','.','.','.','.'],['.','.','4','.','.','1','.','5','.'],['.','.','9','.','.','.','.','1','.'],['.','3','.','9','.','.','8','.','7'],['.','6','.','.','1','.','5','3','.'],['.','.','.','6','5','.','1','.','.'],['9','.','.','8','3','.','.','.','.']]
-      }
-
-
-      //Not that hard
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-               │ 7 │   ┃   │   │   ┃ 9 │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             3 │ 5 │   ┃   │ 6 │   ┃   │   │ 8
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             2 │   │   ┃   │   │   ┃   │   │ 1
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-             4 │   │ 9 ┃   │ 2 │   ┃ 8 │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             6 │   │   ┃   │   │ 8 ┃   │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │ 3 ┃ 1 │   │
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃ 1 │   │   ┃ 7 │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │ 6
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 8 │   ┃   │   │   ┃ 5 │ 3 │ 4
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::Medium1 => {
-         [['.','7','.','.','.','.','9','.','.'],['3','5','.','.','6','.','.','.','8'],['2','.','.','.','.','.','.','.','1'],['4','.','9','.','2','.','8','.','.'],['6','.','.','.','.','8','.','.','.'],['.','.','.','.','.','3','1','.','.'],['.','.','.','1','.','.','7','.','.'],['.','.','.','.','.','.','.','.','6'],['.','8','.','.','.','.','5','3','4']]
-      }
-
-
-      //Not that hard
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-             9 │   │   ┃ 3 │ 7 │   ┃   │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │ 3 ┃ 1 │   │   ┃   │ 4 │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │ 7 ┃   │   │   ┃   │   │
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃   │   │   ┃ 3 │ 1 │ 7
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 8 │   ┃   │   │   ┃ 9 │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 9 │ 2 ┃   │   │   ┃ 4 │   │
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃   │ 9 │   ┃   │ 5 │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             5 │ 6 │   ┃   │   │ 2 ┃   │   │
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │ 4 ┃   │   │ 1
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::Medium2 => {
-         [['9','.','.','3','7','.','.','.','.'],['.','.','3','1','.','.','.','4','.'],['.','.','7','.','.','.','.','.','.'],['.','.','.','.','.','.','3','1','7'],['.','8','.','.','.','.','9','.','.'],['.','9','2','.','.','.','4','.','.'],['.','.','.','.','9','.','.','5','.'],['5','6','.','.','.','2','.','.','.'],['.','.','.','.','.','4','.','.','1']]
-      }
-
-
-        //Impossible puzzle, but seemed possible to previous version of the code
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-               │   │   ┃ 1 │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-             1 │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │ 2 │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │ 1 │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃   │   │ 1 ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::Impossible => {
-         [['.','.','.','1','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['1','.','.','.','.','.','.','.','.'],['.','.','.','.','2','.','.','.','.'],['.','.','.','.','.','.','.','1','.'],['.','.','.','.','.','1','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.']]
-      }
-
-      //Very easy (even I can solve this one)
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-               │ 7 │   ┃ 5 │ 8 │ 3 ┃   │ 2 │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 5 │ 9 ┃ 2 │   │   ┃ 3 │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             3 │ 4 │   ┃   │   │ 6 ┃ 5 │   │ 7
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-             7 │ 9 │ 5 ┃   │   │   ┃ 6 │ 3 │ 2
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │ 3 ┃ 6 │ 9 │ 7 ┃ 1 │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             6 │ 8 │   ┃   │   │ 2 ┃ 7 │   │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-             9 │ 1 │ 4 ┃ 8 │ 3 │ 5 ┃   │ 7 │ 6
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │ 3 │   ┃ 7 │   │ 1 ┃ 4 │ 9 │ 5
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-             5 │ 6 │ 7 ┃ 4 │ 2 │ 9 ┃   │ 1 │ 3
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::Easy => {
-         [['.','7','.','5','8','3','.','2','.'],['.','5','9','2','.','.','3','.','.'],['3','4','.','.','.','6','5','.','7'],['7','9','5','.','.','.','6','3','2'],['.','.','3','6','9','7','1','.','.'],['6','8','.','.','.','2','7','.','.'],['9','1','4','8','3','5','.','7','6'],['.','3','.','7','.','1','4','9','5'],['5','6','7','4','2','9','.','1','3']]
-      }
-
-
-      //Top row 1-9, rest blank (all solutions are equivalent to a possible solution of this one)
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-             1 │ 2 │ 3 ┃ 4 │ 5 │ 6 ┃ 7 │ 8 │ 9
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::TopRow => {
-         [['1','2','3','4','5','6','7','8','9'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.']]
-      }
-
-
-      //Blank board
-      /*
-               ╷   ╷   ╻   ╷   ╷   ╻   ╷   ╷   
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-            ───┼───┼───╂───┼───┼───╂───┼───┼───
-               │   │   ┃   │   │   ┃   │   │  
-               ╵   ╵   ╹   ╵   ╵   ╹   ╵   ╵
-      */
-      PuzzleToSolve::Blank => {
-         [['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.'],['.','.','.','.','.','.','.','.','.']]
-      }
-   };
-
-   initial
-}
\ No newline at end of file
@@ -0,0 +1,491 @@
+use std::str::FromStr;
+
+use crate::{
+    board::{box_shape, Board, MAX_SIZE},
+    cell::Cell,
+    error::SudokuError,
+    location::Location,
+    provenance::Provenance,
+    regions::JigsawRegions,
+    render::{is_cell_mark, BLACK_DOT, WHITE_DOT},
+    symbols::SymbolSet
+};
+
+impl Board {
+    /// Reads a puzzle written one char per cell, either all on one line or as one line per row.
+    /// Whitespace is ignored, `.` and `0` are blank cells, and digits are read using `SymbolSet::digits`.
+    /// The size is inferred from the number of cells (16, 36, 81, 144, 256, 625, ...) and the box shape from `box_shape`.
+    /// Input drawn as a grid, like `Display` prints, is read with `parse_grid` instead.
+    pub fn parse(input: &str) -> Result<Self, SudokuError> {
+        if is_grid(input) {
+            return Self::from_grid(input, None, None);
+        }
+        let cells = cell_chars(input);
+        let cells_per_set = (1..=MAX_SIZE)
+            .find(|size| size * size == cells.len())
+            .ok_or(SudokuError::WrongCellCount { expected: None, found: cells.len() })?;
+        let (rows_per_box, cols_per_box) = box_shape(cells_per_set);
+        Self::from_cell_chars(&cells, rows_per_box, cols_per_box, None)
+    }
+
+    /// Same as `parse`, but with an explicit box shape instead of inferring one, e.g. 2 x 4 boxes for an 8x8 board.
+    pub fn parse_with_shape(input: &str, rows_per_box: usize, cols_per_box: usize) -> Result<Self, SudokuError> {
+        if is_grid(input) {
+            return Self::from_grid(input, None, Some((rows_per_box, cols_per_box)));
+        }
+        Self::from_cell_chars(&cell_chars(input), rows_per_box, cols_per_box, None)
+    }
+
+    /// Same as `parse`, but reads digits with the given symbols, which also set the board's size.
+    /// The box shape is inferred from the size unless one is given.
+    pub fn parse_with_symbols(input: &str, symbols: SymbolSet, shape: Option<(usize, usize)>) -> Result<Self, SudokuError> {
+        if is_grid(input) {
+            return Self::from_grid(input, Some(symbols), shape);
+        }
+        let (rows_per_box, cols_per_box) = shape.unwrap_or_else(|| box_shape(symbols.size()));
+        Self::from_cell_chars(&cell_chars(input), rows_per_box, cols_per_box, Some(symbols))
+    }
+
+    /// Reads a puzzle drawn as a grid, either the box-drawing one `Display` prints or an ASCII one using
+    /// `|` between cols and lines of `-` (or `=` for box edges) and `+` between rows, with or without an outer border.
+    /// Cells are either separated by `|` or by spaces, and are blank if they're `.`, `0`, or all spaces between separators.
+    /// Lines with nothing but grid-drawing chars, like the top and bottom of the `Display` grid, are skipped.
+    ///
+    /// The size is the number of rows, and the box shape is inferred from where the thick separators are
+    /// (`┃` and `━`, or in ASCII `|` and `-` when not every cell has them), falling back to `box_shape`.
+    pub fn parse_grid(input: &str) -> Result<Self, SudokuError> {
+        Self::from_grid(input, None, None)
+    }
+
+    /// Same as `parse_grid`, but reads digits with the given symbols.
+    pub fn parse_grid_with_symbols(input: &str, symbols: SymbolSet) -> Result<Self, SudokuError> {
+        Self::from_grid(input, Some(symbols), None)
+    }
+
+    fn from_grid(input: &str, symbols: Option<SymbolSet>, shape: Option<(usize, usize)>) -> Result<Self, SudokuError> {
+        let lines: Vec<Vec<char>> = input.lines().map(|text| text.chars().collect()).collect();
+        let kinds: Vec<GridLine> = lines.iter().map(|chars| grid_line(chars)).collect();
+
+        //The grid's outer edges, so a blank cell at the start or end of a row isn't mistaken for indentation.
+        let drawn = || lines.iter().zip(kinds.iter()).filter(|(_, kind)| **kind != GridLine::Skipped).map(|(chars, _)| chars);
+        let left = drawn().filter_map(|chars| chars.iter().position(|c| !c.is_whitespace())).min().unwrap_or(0);
+        let right = drawn().filter_map(|chars| chars.iter().rposition(|c| !c.is_whitespace())).max().unwrap_or(0);
+
+        let mut rows: Vec<GridRow> = Vec::new();
+        let mut row_separators = Vec::new();
+        //The weight of the separator below each cell of each row but the last, for reading irregular regions.
+        let mut below = Vec::new();
+        let mut divider = NO_SEPARATOR;
+        let mut divider_lines: Vec<&[char]> = Vec::new();
+        for (index, (chars, kind)) in lines.iter().zip(kinds.iter()).enumerate() {
+            match *kind {
+                GridLine::Row => {
+                    if let Some(last) = rows.last() {
+                        row_separators.push(divider);
+                        below.push(last.cells.iter().map(|&(_, _, column)| divider_weight_at(&divider_lines, column - 1, divider)).collect::<Vec<u8>>());
+                    }
+                    divider = NO_SEPARATOR;
+                    divider_lines.clear();
+                    rows.push(grid_row(chars, index + 1, left, right));
+                }
+                GridLine::Divider(weight) => {
+                    divider = divider.max(weight);
+                    divider_lines.push(chars);
+                }
+                GridLine::Skipped => {}
+            }
+        }
+
+        let cells_per_set = match shape {
+            Some((rows_per_box, cols_per_box)) => rows_per_box * cols_per_box,
+            None => rows.len(),
+        };
+        if rows.is_empty() {
+            return Err(SudokuError::WrongCellCount { expected: None, found: 0 });
+        }
+        //Only a given shape can disagree with the number of rows, which reading the regions relies on.
+        if rows.len() != cells_per_set {
+            let found = rows.iter().map(|row| row.cells.len()).sum();
+            return Err(SudokuError::WrongCellCount { expected: Some(cells_per_set * cells_per_set), found });
+        }
+        let mut col_separators = vec![NO_SEPARATOR; cells_per_set.saturating_sub(1)];
+        for (r, row) in rows.iter().enumerate() {
+            if row.cells.len() != cells_per_set {
+                return Err(SudokuError::WrongRowLength { row: r, expected: cells_per_set, found: row.cells.len() });
+            }
+            for (separator, &weight) in col_separators.iter_mut().zip(row.separators.iter()).filter(|(_, &weight)| weight != DOT_SEPARATOR) {
+                *separator = (*separator).max(weight);
+            }
+        }
+        //A Kropki dot hides the separator it's drawn on, so it takes the weight the separator has in the other rows.
+        for row in rows.iter_mut() {
+            for (weight, &col_weight) in row.separators.iter_mut().zip(col_separators.iter()).filter(|(weight, _)| **weight == DOT_SEPARATOR) {
+                *weight = col_weight.max(THIN_SEPARATOR);
+            }
+        }
+
+        let right_of: Vec<&[u8]> = rows.iter().map(|row| row.separators.as_slice()).collect();
+        let regions = drawn_regions(&right_of, &below, cells_per_set);
+        let inferred_shape = match shape {
+            Some(shape) => Ok(shape),
+            None => match (box_length(&row_separators, cells_per_set), box_length(&col_separators, cells_per_set)) {
+                (Ok(Some(rows_per_box)), Ok(Some(cols_per_box))) if rows_per_box * cols_per_box == cells_per_set => Ok((rows_per_box, cols_per_box)),
+                (Ok(Some(_)), Ok(Some(_))) => Err(SudokuError::UnevenSeparators),
+                (Ok(Some(rows_per_box)), Ok(None)) => Ok((rows_per_box, cells_per_set / rows_per_box)),
+                (Ok(None), Ok(Some(cols_per_box))) => Ok((cells_per_set / cols_per_box, cols_per_box)),
+                (Ok(None), Ok(None)) => Ok(box_shape(cells_per_set)),
+                (Err(error), _) | (_, Err(error)) => Err(error),
+            },
+        };
+        //Separators that aren't evenly spaced are fine if they outline jigsaw regions.
+        let (rows_per_box, cols_per_box) = match (inferred_shape, &regions) {
+            (Err(_), Some(_)) => box_shape(cells_per_set),
+            (inferred_shape, _) => inferred_shape?,
+        };
+
+        let cells: Vec<(char, usize, usize)> = rows.into_iter().flat_map(|row| row.cells).collect();
+        let mut board = Self::from_cell_chars(&cells, rows_per_box, cols_per_box, symbols)?;
+        if let Some(regions) = regions {
+            let is_boxes = regions.regions().iter().all(|region| {
+                let box_of = |location: Location| board.geometry().index(location).map(|cell| board.geometry().box_of(cell));
+                region.iter().all(|&location| box_of(location) == box_of(region[0]))
+            });
+            if !is_boxes {
+                board.try_set_regions(regions)?;
+            }
+        }
+        Ok(board)
+    }
+
+    fn from_cell_chars(cells: &[(char, usize, usize)], rows_per_box: usize, cols_per_box: usize, symbols: Option<SymbolSet>) -> Result<Self, SudokuError> {
+        let mut board = Self::try_new(rows_per_box, cols_per_box)?;
+        if let Some(symbols) = symbols {
+            board.set_symbols(symbols)?;
+        }
+        let cells_per_set = board.cells_per_set();
+        if cells.len() != cells_per_set * cells_per_set {
+            return Err(SudokuError::WrongCellCount { expected: Some(cells_per_set * cells_per_set), found: cells.len() });
+        }
+
+        let symbols = board.symbols().clone();
+        for (index, &(input, line, column)) in cells.iter().enumerate() {
+            if symbols.is_blank(input) {
+                continue;
+            }
+            let digit = symbols.digit_of(input).ok_or(SudokuError::UnexpectedChar { line, column, input })?;
+            board.try_set(Location::Valid(index / cells_per_set, index % cells_per_set), Cell::new_single_digit(cells_per_set, digit).with_provenance(Provenance::Given))?;
+        }
+
+        Ok(board)
+    }
+}
+
+impl FromStr for Board {
+    type Err = SudokuError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse(input)
+    }
+}
+
+/// Returns every non-whitespace char with its (1-based) line and column.
+fn cell_chars(input: &str) -> Vec<(char, usize, usize)> {
+    input
+        .lines()
+        .enumerate()
+        .flat_map(|(line, text)| {
+            text.chars()
+                .enumerate()
+                .filter(|(_, c)| !c.is_whitespace())
+                .map(move |(column, c)| (c, line + 1, column + 1))
+        })
+        .collect()
+}
+
+pub(crate) const NO_SEPARATOR: u8 = 0;
+const THIN_SEPARATOR: u8 = 1;
+pub(crate) const THICK_SEPARATOR: u8 = 2;
+/// A Kropki dot drawn in place of a vertical separator, until it's given the weight of the separators above and below it.
+const DOT_SEPARATOR: u8 = u8::MAX;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GridLine {
+    //A row of cells.
+    Row,
+    //A line between rows, with the weight of its separator.
+    Divider(u8),
+    //Blank, or only grid-drawing chars that don't separate anything, like the `╷`s along the top.
+    Skipped,
+}
+
+struct GridRow {
+    cells: Vec<(char, usize, usize)>,
+    //The weight of the separator after each cell but the last.
+    separators: Vec<u8>,
+}
+
+/// Returns true if the input draws a grid rather than listing the cells.
+fn is_grid(input: &str) -> bool {
+    input.chars().any(is_grid_char)
+}
+
+/// Chars that draw a grid rather than fill a cell.
+fn is_grid_char(input: char) -> bool {
+    matches!(input, '|' | '+' | '-' | '=') || ('\u{2500}'..='\u{257F}').contains(&input)
+}
+
+fn vertical_weight(input: char) -> Option<u8> {
+    match input {
+        '│' | '┊' | '|' => Some(THIN_SEPARATOR),
+        '┃' | '┋' | '║' => Some(THICK_SEPARATOR),
+        WHITE_DOT | BLACK_DOT => Some(DOT_SEPARATOR),
+        _ => None,
+    }
+}
+
+fn horizontal_weight(input: char) -> Option<u8> {
+    match input {
+        '─' | '┈' | '-' => Some(THIN_SEPARATOR),
+        '━' | '┉' | '═' | '=' => Some(THICK_SEPARATOR),
+        _ => None,
+    }
+}
+
+fn grid_line(chars: &[char]) -> GridLine {
+    //Lines across the grid can have killer cage sums written on them, so digits there aren't cells.
+    let is_across = chars.iter().any(|&c| matches!(c, '─' | '━' | '┈' | '┉' | '═' | '╷' | '╻' | '╵' | '╹'));
+    let has_cell = !is_across && chars.iter().any(|&c| !c.is_whitespace() && !is_grid_char(c));
+    let divider = chars.iter().filter_map(|&c| horizontal_weight(c)).max();
+    match divider {
+        Some(weight) if !has_cell => GridLine::Divider(weight),
+        _ if has_cell || chars.iter().any(|&c| vertical_weight(c).is_some()) => GridLine::Row,
+        _ => GridLine::Skipped,
+    }
+}
+
+/// Splits a row into cells at its vertical separators, where `left` and `right` are the first and last columns of the grid.
+/// Text between two separators is one blank cell if it's all spaces, otherwise one cell per char.
+fn grid_row(chars: &[char], line: usize, left: usize, right: usize) -> GridRow {
+    //Each separator as (first column, last column, weight), with adjacent ones like `||` read as one thick separator.
+    let mut separators: Vec<(usize, usize, u8)> = Vec::new();
+    for (column, &c) in chars.iter().enumerate() {
+        if let Some(weight) = vertical_weight(c) {
+            match separators.last_mut() {
+                Some(last) if last.1 + 1 == column => *last = (last.0, column, THICK_SEPARATOR),
+                _ => separators.push((column, column, weight)),
+            }
+        }
+    }
+
+    //Each run of text between separators as (first column, end column, weight of the separator before it).
+    //A separator right at the left or right edge of the grid is a border, with no cell beyond it.
+    let mut spans = Vec::new();
+    let mut start = left;
+    let mut weight_before = NO_SEPARATOR;
+    for &(first, last, weight) in separators.iter() {
+        if first > start {
+            spans.push((start, first, weight_before));
+        }
+        start = last + 1;
+        weight_before = weight;
+    }
+    if start <= right {
+        spans.push((start, right + 1, weight_before));
+    }
+
+    let mut row = GridRow { cells: Vec::new(), separators: Vec::new() };
+    for (start, end, weight) in spans {
+        let text = chars.get(start..end.min(chars.len())).unwrap_or(&[]);
+        if !row.cells.is_empty() {
+            row.separators.push(weight);
+        }
+        if text.iter().all(|&c| is_cell_padding(c)) {
+            row.cells.push(('.', line, start + 1));
+            continue;
+        }
+        for (offset, &c) in text.iter().enumerate().filter(|(_, &c)| !is_cell_padding(c)) {
+            if row.cells.len() > row.separators.len() {
+                row.separators.push(NO_SEPARATOR);
+            }
+            row.cells.push((c, line, start + offset + 1));
+        }
+    }
+    row
+}
+
+/// Returns the weight of the separator drawn at the column of the lines between two rows (or just after a number there),
+/// or the heaviest weight drawn anywhere in them if none is drawn at that column.
+fn divider_weight_at(lines: &[&[char]], column: usize, heaviest: u8) -> u8 {
+    lines
+        .iter()
+        //Skip past a killer cage sum or Kropki dot written over the line.
+        .filter_map(|chars| chars.get(column..)?.iter().find(|&&c| !c.is_ascii_digit() && c != WHITE_DOT && c != BLACK_DOT).and_then(|&c| horizontal_weight(c)))
+        .max()
+        .unwrap_or(heaviest)
+}
+
+/// Reads the regions outlined by a grid's heaviest separators, given the weight of the separator right of and below each cell.
+/// Returns `None` if they aren't heavier than the rest or don't outline connected regions of a cell per row.
+fn drawn_regions(right_of: &[&[u8]], below: &[Vec<u8>], size: usize) -> Option<JigsawRegions> {
+    let weights = || right_of.iter().copied().chain(below.iter().map(|weights| weights.as_slice())).flatten();
+    let thickest = *weights().max()?;
+    if weights().all(|&weight| weight == thickest) {
+        return None;
+    }
+
+    //Number the regions by flood filling across the lighter separators, in the order their first cells appear.
+    let mut region_of = vec![usize::MAX; size * size];
+    let mut num_regions = 0;
+    for first in 0..size * size {
+        if region_of[first] != usize::MAX {
+            continue;
+        }
+        region_of[first] = num_regions;
+        let mut reached = vec![first];
+        while let Some(cell) = reached.pop() {
+            let (r, c) = (cell / size, cell % size);
+            let open = |weight: Option<&u8>| weight.is_some_and(|&weight| weight < thickest);
+            let neighbors = [
+                (r > 0 && open(below.get(r.wrapping_sub(1)).and_then(|weights| weights.get(c)))).then(|| cell - size),
+                open(below.get(r).and_then(|weights| weights.get(c))).then_some(cell + size),
+                (c > 0 && open(right_of[r].get(c.wrapping_sub(1)))).then(|| cell - 1),
+                open(right_of[r].get(c)).then_some(cell + 1),
+            ];
+            for neighbor in neighbors.into_iter().flatten() {
+                if region_of[neighbor] == usize::MAX {
+                    region_of[neighbor] = num_regions;
+                    reached.push(neighbor);
+                }
+            }
+        }
+        num_regions += 1;
+    }
+    JigsawRegions::from_region_of(size, region_of).ok()
+}
+
+/// Returns true for chars around a cell's digit that aren't part of it: spaces, marks like the `╲` along a diagonal
+/// or the `●`, `○`, and arrows along thermometers and arrows, and the `░` shading of extra regions.
+fn is_cell_padding(input: char) -> bool {
+    input.is_whitespace() || is_grid_char(input) || input == '░' || is_cell_mark(input)
+}
+
+/// Returns how many cells apart the thickest separators are, or `None` if every separator is the same so there's nothing to go on.
+pub(crate) fn box_length(separators: &[u8], cells_per_set: usize) -> Result<Option<usize>, SudokuError> {
+    let Some(&thickest) = separators.iter().max() else {
+        return Ok(None);
+    };
+    if separators.iter().all(|&weight| weight == thickest) {
+        return Ok(None);
+    }
+
+    let length = separators.iter().take_while(|&&weight| weight != thickest).count() + 1;
+    let is_even = cells_per_set.is_multiple_of(length)
+        && separators.iter().enumerate().all(|(index, &weight)| (weight == thickest) == (index + 1).is_multiple_of(length));
+    if is_even {
+        Ok(Some(length))
+    }
+    else {
+        Err(SudokuError::UnevenSeparators)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_the_size_from_the_number_of_cells() {
+        for size in [4, 6, 9, 12, 16, 25] {
+            let board = Board::parse(&".".repeat(size * size)).unwrap_or_else(|error| panic!("{error}"));
+            assert_eq!(board.cells_per_set(), size);
+            assert_eq!((board.rows_per_box(), board.cols_per_box()), box_shape(size));
+        }
+    }
+
+    #[test]
+    fn reads_rows_and_blanks() {
+        let board: Board = "1.34\n34 02\n\n2.43\n4 3 0 1".parse().unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!(board.to_line(), "1.3434.22.4343.1");
+        assert!(board.get(Location::Valid(0, 0)).is_given());
+        assert!(!board.get(Location::Valid(1, 2)).solved);
+
+        //`0` is a digit when the symbols have one.
+        let board = Board::parse_with_symbols(&"0".repeat(256), SymbolSet::hex(), None).unwrap_or_else(|error| panic!("{error}"));
+        assert!(board.get(Location::Valid(15, 15)).is_given());
+    }
+
+    #[test]
+    fn reports_where_an_unexpected_char_is() {
+        assert_eq!(Board::parse("1.34\n34x2\n2.43\n43.1").err(), Some(SudokuError::UnexpectedChar { line: 2, column: 3, input: 'x' }));
+        assert_eq!(Board::parse("1 . 3 4\n3 4 . 2\n2 . 4 3\n4 3 . 5").err(), Some(SudokuError::UnexpectedChar { line: 4, column: 7, input: '5' }));
+    }
+
+    #[test]
+    fn rejects_the_wrong_number_of_cells() {
+        assert_eq!(Board::parse(&".".repeat(80)).err(), Some(SudokuError::WrongCellCount { expected: None, found: 80 }));
+        assert_eq!(Board::parse_with_shape(&".".repeat(81), 2, 2).err(), Some(SudokuError::WrongCellCount { expected: Some(16), found: 81 }));
+    }
+
+    #[test]
+    fn a_given_shape_overrides_the_inferred_one() {
+        let line = ".".repeat(64);
+        let inferred = Board::parse(&line).unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!((inferred.rows_per_box(), inferred.cols_per_box()), (2, 4));
+        let given = Board::parse_with_shape(&line, 4, 2).unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!((given.rows_per_box(), given.cols_per_box()), (4, 2));
+    }
+
+    /// A solved board of the given shape with every third cell blanked out.
+    fn partly_filled(rows_per_box: usize, cols_per_box: usize) -> Board {
+        let mut board = Board::new(rows_per_box, cols_per_box);
+        board.fast_solve();
+        let size = board.cells_per_set();
+        for index in (0..size * size).step_by(3) {
+            board.set(Location::Valid(index / size, index % size), Cell::new(size));
+        }
+        board
+    }
+
+    #[test]
+    fn display_round_trips_through_parse_grid() {
+        for (rows_per_box, cols_per_box) in [(3, 3), (3, 4), (2, 4)] {
+            let board = partly_filled(rows_per_box, cols_per_box);
+            let parsed = Board::parse_grid(&board.to_string()).unwrap_or_else(|error| panic!("{error}\n{board}"));
+            assert_eq!((parsed.rows_per_box(), parsed.cols_per_box()), (rows_per_box, cols_per_box));
+            assert_eq!(parsed.to_line(), board.to_line());
+            //`parse` recognizes a grid too.
+            assert_eq!(Board::parse(&board.to_string()).map(|board| board.to_line()), Ok(parsed.to_line()));
+        }
+    }
+
+    #[test]
+    fn reads_an_ascii_grid() {
+        let grid = "+-----+-----+\n| 1 . | 3 4 |\n| 3 4 | . 2 |\n+-----+-----+\n| 2 . | 4 3 |\n| 4 3 | 0 1 |\n+-----+-----+\n";
+        let board = Board::parse_grid(grid).unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!((board.rows_per_box(), board.cols_per_box()), (2, 2));
+        assert_eq!(board.to_line(), "1.3434.22.4343.1");
+    }
+
+    #[test]
+    fn infers_the_box_shape_from_the_thick_separators() {
+        //Both differ from what `box_shape` would pick for the size.
+        for (rows_per_box, cols_per_box) in [(3, 2), (4, 2)] {
+            assert_ne!(box_shape(rows_per_box * cols_per_box), (rows_per_box, cols_per_box));
+            let board = partly_filled(rows_per_box, cols_per_box);
+            let parsed = Board::parse_grid(&board.to_string()).unwrap_or_else(|error| panic!("{error}\n{board}"));
+            assert_eq!((parsed.rows_per_box(), parsed.cols_per_box()), (rows_per_box, cols_per_box));
+        }
+    }
+
+    #[test]
+    fn uneven_separators() {
+        let grid = "1┃2│3│4\n─╂─┼─┼─\n3┃4│1│2\n━╋━┿━┿━\n2┃1│4│3\n─╂─┼─┼─\n4┃3│2│1\n";
+        assert_eq!(Board::parse_grid(grid).err(), Some(SudokuError::UnevenSeparators));
+    }
+
+    #[test]
+    fn too_few_rows_for_the_given_shape() {
+        let grid = "1│2│3┃4│5│6┃7│8│9\n─┼─┼─╂─┼─┼─╂─┼─┼─\n4│5│6┃7│8│9┃1│2│3\n";
+        assert_eq!(Board::parse_with_shape(grid, 3, 3).err(), Some(SudokuError::WrongCellCount { expected: Some(81), found: 18 }));
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{
+    board::{box_shape, Board},
+    cell::Cell,
+    error::SudokuError,
+    location::Location,
+    provenance::Provenance,
+    parse::{box_length, NO_SEPARATOR, THICK_SEPARATOR},
+    symbols::SymbolSet
+};
+
+/// Marks a cell whose `solved` flag isn't what its candidates suggest, i.e. an unsolved cell with one candidate left.
+const FLIPPED_MARK: char = '*';
+/// Written for a cell with no candidates left.
+const NO_CANDIDATES: &str = "-";
+
+impl Board {
+    /// Writes every cell's candidates as a grid in the style of HoDoKu and SudokuWiki, like
+    ///
+    /// ```text
+    /// .----------------.
+    /// | 4    36   368  |
+    /// :----------------+
+    /// ```
+    ///
+    /// with `|` around each box and the candidates of each cell written together using the board's symbols.
+    /// A cell with one candidate is read back as solved, so an unsolved one is written with a trailing `*` (e.g. `5*`);
+    /// `*` likewise marks the (unusual) solved cells with more or less than one candidate.
+    /// A cell with no candidates is written as `-`.
+    /// `parse_pencil_marks` reads this back into exactly the same cells.
+    pub fn to_pencil_marks(&self) -> String {
+        let size = self.cells_per_set();
+        let mut tokens = Vec::with_capacity(size * size);
+        for r in 0..size {
+            for c in 0..size {
+                let cell = self.get(Location::Valid(r, c));
+                let mut token: String = cell
+                    .possibilities
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &possible)| possible)
+                    .filter_map(|(digit, _)| self.symbols().char_of(digit))
+                    .collect();
+                if token.is_empty() {
+                    token.push_str(NO_CANDIDATES);
+                }
+                if cell.solved != (cell.num_possibilities() == 1) {
+                    token.push(FLIPPED_MARK);
+                }
+                tokens.push(token);
+            }
+        }
+
+        //Each col is as wide as its longest cell, so the cells line up.
+        let widths: Vec<usize> = (0..size)
+            .map(|c| (0..size).map(|r| tokens[r * size + c].chars().count()).max().unwrap_or(1))
+            .collect();
+        let box_widths: Vec<usize> = widths
+            .chunks(self.cols_per_box())
+            .map(|box_cols| box_cols.iter().sum::<usize>() + box_cols.len() + 1)
+            .collect();
+        let divider = |end: char, middle: char| {
+            let mut line = String::new();
+            for (index, &width) in box_widths.iter().enumerate() {
+                line.push(if index == 0 { end } else { middle });
+                line += &"-".repeat(width);
+            }
+            line.push(end);
+            line.push('\n');
+            line
+        };
+
+        let mut output = divider('.', '.');
+        for r in 0..size {
+            if r > 0 && r % self.rows_per_box() == 0 {
+                output += &divider(':', '+');
+            }
+            for c in 0..size {
+                if c % self.cols_per_box() == 0 {
+                    output += "| ";
+                }
+                output += &format!("{:<width$} ", tokens[r * size + c], width = widths[c]);
+            }
+            output += "|\n";
+        }
+        output += &divider('\'', '\'');
+        output
+    }
+
+    /// Reads a pencil-mark grid like the one `to_pencil_marks` writes, which HoDoKu and SudokuWiki also use.
+    /// Cells are separated by spaces and boxes by `|`, and lines of only `-`, `.`, `:`, `+`, `'`, `=`, and `|` are skipped
+    /// apart from marking where boxes end. The size is the number of rows, and the box shape comes from the `|`s and
+    /// the lines between rows, falling back to `box_shape` if they don't say.
+    /// Digits are read using `SymbolSet::digits`.
+    pub fn parse_pencil_marks(input: &str) -> Result<Self, SudokuError> {
+        Self::from_pencil_marks(input, None)
+    }
+
+    /// Same as `parse_pencil_marks`, but reads digits with the given symbols.
+    pub fn parse_pencil_marks_with_symbols(input: &str, symbols: SymbolSet) -> Result<Self, SudokuError> {
+        Self::from_pencil_marks(input, Some(symbols))
+    }
+
+    fn from_pencil_marks(input: &str, symbols: Option<SymbolSet>) -> Result<Self, SudokuError> {
+        //Each cell as (text, line, column), along with whether a box ends after each cell and each row.
+        let mut rows: Vec<Vec<(&str, usize, usize)>> = Vec::new();
+        let mut col_separators: Vec<u8> = Vec::new();
+        let mut row_separators: Vec<u8> = Vec::new();
+        let mut divider = NO_SEPARATOR;
+        for (index, text) in input.lines().enumerate() {
+            let trimmed = text.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if !trimmed.contains(char::is_whitespace) && trimmed.chars().all(|c| "-.:+'=|".contains(c)) {
+                divider = THICK_SEPARATOR;
+                continue;
+            }
+
+            let mut row = Vec::new();
+            let mut separators = Vec::new();
+            let mut separator = NO_SEPARATOR;
+            let mut column = 1;
+            for segment in text.split('|') {
+                let mut start = None;
+                for (offset, c) in segment.char_indices().chain(std::iter::once((segment.len(), ' '))) {
+                    match (start, c.is_whitespace()) {
+                        (None, false) => start = Some(offset),
+                        (Some(first), true) => {
+                            if !row.is_empty() {
+                                separators.push(separator);
+                            }
+                            separator = NO_SEPARATOR;
+                            row.push((&segment[first..offset], index + 1, column + segment[..first].chars().count()));
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                }
+                if !row.is_empty() {
+                    separator = THICK_SEPARATOR;
+                }
+                column += segment.chars().count() + 1;
+            }
+
+            if !rows.is_empty() {
+                row_separators.push(divider);
+            }
+            divider = NO_SEPARATOR;
+            if col_separators.is_empty() {
+                col_separators = separators;
+            }
+            rows.push(row);
+        }
+
+        let size = rows.len();
+        if size == 0 {
+            return Err(SudokuError::WrongCellCount { expected: None, found: 0 });
+        }
+        for (r, row) in rows.iter().enumerate() {
+            if row.len() != size {
+                return Err(SudokuError::WrongRowLength { row: r, expected: size, found: row.len() });
+            }
+        }
+        let (rows_per_box, cols_per_box) = match (box_length(&row_separators, size)?, box_length(&col_separators, size)?) {
+            (Some(rows_per_box), Some(cols_per_box)) if rows_per_box * cols_per_box == size => (rows_per_box, cols_per_box),
+            (Some(_), Some(_)) => return Err(SudokuError::UnevenSeparators),
+            (Some(rows_per_box), None) => (rows_per_box, size / rows_per_box),
+            (None, Some(cols_per_box)) => (size / cols_per_box, cols_per_box),
+            (None, None) => box_shape(size),
+        };
+
+        let mut board = Self::try_new(rows_per_box, cols_per_box)?;
+        if let Some(symbols) = symbols {
+            board.set_symbols(symbols)?;
+        }
+        let symbols = board.symbols().clone();
+        for (r, row) in rows.into_iter().enumerate() {
+            for (c, (text, line, column)) in row.into_iter().enumerate() {
+                let (candidates, flipped) = match text.strip_suffix(FLIPPED_MARK) {
+                    Some(candidates) => (candidates, true),
+                    None => (text, false),
+                };
+                let mut cell = Cell { possibilities: vec![false; size], solved: false, provenance: Provenance::Unknown };
+                if candidates != NO_CANDIDATES {
+                    for (offset, input) in candidates.chars().enumerate() {
+                        let digit = symbols.digit_of(input).ok_or(SudokuError::UnexpectedChar { line, column: column + offset, input })?;
+                        cell.possibilities[digit] = true;
+                    }
+                }
+                cell.solved = (cell.num_possibilities() == 1) != flipped;
+                board.try_set(Location::Valid(r, c), cell)?;
+            }
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same_cells(a: &Board, b: &Board) {
+        assert_eq!((a.rows_per_box(), a.cols_per_box()), (b.rows_per_box(), b.cols_per_box()));
+        let size = a.cells_per_set();
+        for r in 0..size {
+            for c in 0..size {
+                let location = Location::Valid(r, c);
+                assert!(a.get(location).possibilities == b.get(location).possibilities, "candidates differ at {location}");
+                assert_eq!(a.get(location).solved, b.get(location).solved, "solved differs at {location}");
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_keeps_every_cell() {
+        let mut board = Board::new(3, 2);
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(6, 0));
+        board.set(Location::Valid(2, 3), Cell::new_single_digit(6, 4));
+        board.fast_reduction_loop(false);
+
+        //An unsolved cell with one candidate, and a cell with none.
+        let mut flipped = Cell::new_single_digit(6, 2);
+        flipped.solved = false;
+        board.set(Location::Valid(5, 5), flipped);
+        board.set(Location::Valid(4, 4), Cell { possibilities: vec![false; 6], solved: false, provenance: Provenance::Unknown });
+
+        let text = board.to_pencil_marks();
+        assert!(text.contains("3*"));
+        assert!(text.contains(" - "));
+        let parsed = Board::parse_pencil_marks(&text).unwrap_or_else(|error| panic!("{error}\n{text}"));
+        assert_same_cells(&board, &parsed);
+    }
+
+    #[test]
+    fn reads_a_grid_without_separators() {
+        let board = Board::parse_pencil_marks("1 234 234 234\n234 1234 34 2\n- 4 1 3\n2 3 4 1").unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!((board.rows_per_box(), board.cols_per_box()), (2, 2));
+        assert!(board.get(Location::Valid(0, 0)).solved);
+        assert_eq!(board.get(Location::Valid(1, 1)).num_possibilities(), 4);
+        assert_eq!(board.get(Location::Valid(2, 0)).num_possibilities(), 0);
+    }
+
+    #[test]
+    fn reads_and_writes_custom_symbols() {
+        let mut board = Board::new(2, 2);
+        board.set_symbols(SymbolSet::new("WORD").unwrap_or_else(|error| panic!("{error}"))).unwrap_or_else(|error| panic!("{error}"));
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(4, 3));
+        let text = board.to_pencil_marks();
+        assert!(text.starts_with(".") && text.contains("| D "));
+        let parsed = Board::parse_pencil_marks_with_symbols(&text, board.symbols().clone()).unwrap_or_else(|error| panic!("{error}"));
+        assert_same_cells(&board, &parsed);
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        assert_eq!(
+            Board::parse_pencil_marks("1 2 3 4\n1 2 3 4\n1 2 3 4\n1 2 x 4").err(),
+            Some(SudokuError::UnexpectedChar { line: 4, column: 5, input: 'x' })
+        );
+        assert_eq!(
+            Board::parse_pencil_marks("1 2 3 4\n1 2 3\n1 2 3 4\n1 2 3 4").err(),
+            Some(SudokuError::WrongRowLength { row: 1, expected: 4, found: 3 })
+        );
+        assert_eq!(Board::parse_pencil_marks("\n").err(), Some(SudokuError::WrongCellCount { expected: None, found: 0 }));
+    }
+}
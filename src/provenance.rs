@@ -0,0 +1,94 @@
+use std::fmt::Display;
+
+/// How a cell came to hold its digit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Provenance {
+    /// Not recorded, e.g. for unsolved cells and cells made with `Cell::new_single_digit`.
+    #[default]
+    Unknown,
+    /// A clue of the puzzle.
+    Given,
+    /// Placed by the solver using the technique.
+    Technique(Technique),
+    /// Placed by the solver as a guess, `depth` guesses deep (so 1 for a guess made before any other).
+    Guess { depth: usize },
+    /// Entered by a player with `Board::try_enter`.
+    Player,
+}
+
+/// The ways the solver places a digit without guessing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Technique {
+    /// The cell has only one candidate left.
+    NakedSingle,
+    /// The digit has only one place left in a row, col, or box.
+    HiddenSingle,
+}
+
+impl Technique {
+    /// Lowercase name, e.g. "naked single".
+    pub fn name(self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "naked single",
+            Technique::HiddenSingle => "hidden single",
+        }
+    }
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Provenance::Unknown => write!(f, "unknown"),
+            Provenance::Given => write!(f, "given"),
+            Provenance::Technique(technique) => write!(f, "{technique}"),
+            Provenance::Guess { depth } => write!(f, "guess at depth {depth}"),
+            Provenance::Player => write!(f, "player"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, error::SudokuError, location::Location};
+
+    #[test]
+    fn names() {
+        assert_eq!(Provenance::default().to_string(), "unknown");
+        assert_eq!(Provenance::Given.to_string(), "given");
+        assert_eq!(Provenance::Technique(Technique::HiddenSingle).to_string(), "hidden single");
+        assert_eq!(Provenance::Guess { depth: 2 }.to_string(), "guess at depth 2");
+        assert_eq!(Provenance::Player.to_string(), "player");
+    }
+
+    #[test]
+    fn solving_records_how_each_cell_was_placed() {
+        let mut board = Board::parse("1.34\n34.2\n2.43\n43.1").unwrap_or_else(|error| panic!("{error}"));
+        board.fast_solve();
+        assert!(board.get(Location::Valid(0, 0)).is_given());
+        assert_eq!(board.get(Location::Valid(0, 1)).provenance, Provenance::Technique(Technique::NakedSingle));
+        assert_eq!(board.get(Location::Valid(1, 2)).provenance, Provenance::Technique(Technique::NakedSingle));
+    }
+
+    #[test]
+    fn players_can_only_change_non_givens() {
+        let mut board = Board::parse("1...\n....\n....\n....").unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!(board.try_enter(Location::Valid(0, 0), 1), Err(SudokuError::GivenCell(Location::Valid(0, 0))));
+        assert_eq!(board.try_enter(Location::Valid(0, 1), 1), Ok(()));
+        assert_eq!(board.get(Location::Valid(0, 1)).provenance, Provenance::Player);
+
+        board.set(Location::Valid(3, 3), board.get(Location::Valid(0, 1)).clone().with_provenance(Provenance::Unknown));
+        board.mark_givens();
+        assert!(board.get(Location::Valid(0, 1)).is_given() && board.get(Location::Valid(3, 3)).is_given());
+        assert!(!board.get(Location::Valid(2, 2)).is_given());
+    }
+}
@@ -1,89 +1,298 @@
-use rand::{rngs::ThreadRng, Rng};
+use std::{fmt::Display, str::FromStr, sync::Arc};
 
-use crate::{board::Board, cell::Cell, location::Location};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
-const DEBUG: bool = true;
+use crate::{
+    board::Board,
+    cell::Cell,
+    constraint::Constraint,
+    error::SudokuError,
+    location::Location,
+    rating::Difficulty,
+    regions::JigsawRegions
+};
 
-/// Creates puzzles that are guaranteed to have at least one solution.
+const DEBUG: bool = false;
+
+/// How many solution grids to try before giving up on hitting the requested difficulty exactly.
+const MAX_DIFFICULTY_ATTEMPTS: usize = 20;
+
+/// Which cells must be removed together so the givens form a symmetric pattern.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// 180 degree rotation.
+    Rotational,
+    /// 90 degree rotation.
+    Quarter,
+    /// Mirrored left to right.
+    Horizontal,
+    /// Mirrored top to bottom.
+    Vertical,
+    /// Mirrored along the main diagonal.
+    Diagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 6] = [Symmetry::None, Symmetry::Rotational, Symmetry::Quarter, Symmetry::Horizontal, Symmetry::Vertical, Symmetry::Diagonal];
+
+    /// Lowercase name, as accepted by `from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::None => "none",
+            Symmetry::Rotational => "rotational",
+            Symmetry::Quarter => "quarter",
+            Symmetry::Horizontal => "horizontal",
+            Symmetry::Vertical => "vertical",
+            Symmetry::Diagonal => "diagonal",
+        }
+    }
+
+    /// Returns the given cell and every cell it maps to under this symmetry, without duplicates.
+    pub fn orbit(self, cells_per_set: usize, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let last = cells_per_set - 1;
+        let images = match self {
+            Symmetry::None => vec![(row, col)],
+            Symmetry::Rotational => vec![(row, col), (last - row, last - col)],
+            Symmetry::Quarter => vec![(row, col), (col, last - row), (last - row, last - col), (last - col, row)],
+            Symmetry::Horizontal => vec![(row, col), (row, last - col)],
+            Symmetry::Vertical => vec![(row, col), (last - row, col)],
+            Symmetry::Diagonal => vec![(row, col), (col, row)],
+        };
+        let mut orbit = Vec::with_capacity(images.len());
+        for image in images {
+            if !orbit.contains(&image) {
+                orbit.push(image);
+            }
+        }
+        orbit
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|symmetry| symmetry.name().eq_ignore_ascii_case(input))
+            .ok_or_else(|| format!("unknown symmetry '{input}' (expected none, rotational, quarter, horizontal, vertical, or diagonal)"))
+    }
+}
+
+/// Creates puzzles that are guaranteed to have exactly one solution.
 pub struct PuzzleGenerator {
-    rng: ThreadRng,
+    rng: StdRng,
+    symmetry: Symmetry,
+    difficulty: Option<Difficulty>,
+    constraints: Vec<Arc<dyn Constraint>>,
+    regions: Option<JigsawRegions>,
+}
+
+impl Default for PuzzleGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PuzzleGenerator {
+    /// Creates a generator with a random seed.
     pub fn new() -> Self {
+        Self::from_rng(StdRng::from_entropy())
+    }
+
+    /// Creates a generator that makes the same puzzles every time it's given the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: StdRng) -> Self {
         Self {
-            rng: ThreadRng::default(),
+            rng,
+            symmetry: Symmetry::None,
+            difficulty: None,
+            constraints: Vec::new(),
+            regions: None,
         }
     }
 
-    /// Generates a puzzle with at least one solution by adding random digits
-    /// to a blank board, solving it, then removing random digits.
+    /// Makes the givens of generated puzzles follow the given symmetry.
+    pub fn symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// Only removes digits while generated puzzles stay at or below the given difficulty,
+    /// retrying with new solutions until one lands on it exactly.
+    /// Either way, digits are removed until no more can be removed.
+    pub fn difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = Some(difficulty);
+        self
+    }
+
+    /// Adds a rule that generated puzzles follow on top of the rows, cols, and boxes.
+    pub fn constraint<C: Constraint + 'static>(mut self, constraint: C) -> Self {
+        self.constraints.push(Arc::new(constraint));
+        self
+    }
+
+    /// Same as `constraint`, for a constraint that's already shared, e.g. one from `Variant::constraint`.
+    pub fn shared_constraint(mut self, constraint: Arc<dyn Constraint>) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Makes generated puzzles jigsaw puzzles, with the given regions in place of the boxes.
+    /// The regions must be for the size of board being generated.
+    pub fn regions(mut self, regions: JigsawRegions) -> Self {
+        self.regions = Some(regions);
+        self
+    }
+
+    /// Generates a puzzle with exactly one solution by adding random digits
+    /// to a blank board, solving it, then removing random digits while the solution stays unique.
+    /// Panics if the box shape isn't supported; see `try_generate_puzzle`.
     pub fn generate_puzzle(&mut self, rows_per_box: usize, cols_per_box: usize) -> Board {
-        let cells_per_set = rows_per_box * cols_per_box;
-        let total_size = cells_per_set * cells_per_set;
-        if DEBUG {
-            println!("Generating {} x {} puzzle...", cells_per_set, cells_per_set);
+        match self.try_generate_puzzle(rows_per_box, cols_per_box) {
+            Ok(board) => board,
+            Err(error) => panic!("{error}"),
         }
+    }
 
-        let mut solved = false;
-        let mut board= Board::new(rows_per_box, cols_per_box);
-        while !solved {
-            board = Board::new(rows_per_box, cols_per_box);
-            //Conjecture: any puzzle with [size - 1] or less clues has at least one solution.
-            //For a 9x9 grid, you could get a contradiction by placing the digits 1-8 along the top row from left to right,
-            //then placing a 9 anywhere in box 3 other than R1C9
-            let mut placed_positions = Vec::with_capacity(cells_per_set);
-
-            for digit in 0..cells_per_set-1 {
-                let mut r = self.rng.gen_range(0..cells_per_set);
-                let mut c = self.rng.gen_range(0..cells_per_set);
-                while placed_positions.contains(&(r, c)) {
-                    r = self.rng.gen_range(0..cells_per_set);
-                    c = self.rng.gen_range(0..cells_per_set);
-                }
-                placed_positions.push((r, c));
+    /// Same as `generate_puzzle`, but returns an error if the box shape isn't supported, or a constraint
+    /// (or the jigsaw regions) doesn't fit it or can't be followed at all.
+    pub fn try_generate_puzzle(&mut self, rows_per_box: usize, cols_per_box: usize) -> Result<Board, SudokuError> {
+        //Check the shape and constraints before doing anything else.
+        let mut blank = Board::try_new(rows_per_box, cols_per_box)?;
+        for constraint in self.constraints.iter() {
+            blank.try_add_shared_constraint(Arc::clone(constraint))?;
+        }
+        if let Some(regions) = &self.regions {
+            blank.try_set_regions(regions.clone())?;
+        }
+        //Otherwise looking for a solution to start from would never end.
+        if (!self.constraints.is_empty() || self.regions.is_some()) && !blank.clone().fast_solve_with_stats().solved {
+            return Err(SudokuError::Unsatisfiable);
+        }
+        let Some(target) = self.difficulty else {
+            let solution = generate_solution(&mut self.rng, &blank);
+            return Ok(self.remove_digits(solution));
+        };
 
-                let given_digit = Cell::new_single_digit(cells_per_set, digit);
-                if DEBUG {
-                    println!("Placing {given_digit} at {r}, {c}");
-                }
-                board.set(Location::Valid(r, c), given_digit);
+        //Keep the attempt whose difficulty is closest to the target.
+        let mut best: Option<(Board, usize)> = None;
+        for _ in 0..MAX_DIFFICULTY_ATTEMPTS {
+            let solution = generate_solution(&mut self.rng, &blank);
+            let puzzle = self.remove_digits(solution);
+            let difficulty = puzzle.rate().map_or(Difficulty::Easy, |rating| rating.difficulty);
+            let distance = (difficulty as usize).abs_diff(target as usize);
+            if distance == 0 {
+                return Ok(puzzle);
+            }
+            if best.as_ref().is_none_or(|(_, best_distance)| distance < *best_distance) {
+                best = Some((puzzle, distance));
             }
+        }
 
-            if DEBUG {
-                println!("{board}");
-                println!("Solving...");
+        best.map(|(puzzle, _)| puzzle).ok_or(SudokuError::NoPuzzleGenerated)
+    }
+
+    /// Removes random digits (in groups that follow the symmetry) as long as the puzzle keeps exactly one solution
+    /// and doesn't get harder than the target difficulty.
+    fn remove_digits(&mut self, solution: Board) -> Board {
+        let cells_per_set = solution.cells_per_set();
+        let mut board = solution;
+        let mut locations: Vec<(usize, usize)> = (0..cells_per_set * cells_per_set).map(|index| (index / cells_per_set, index % cells_per_set)).collect();
+        locations.shuffle(&mut self.rng);
+
+        for (r, c) in locations {
+            let orbit = self.symmetry.orbit(cells_per_set, r, c);
+            if orbit.iter().any(|&(r, c)| !board.get(Location::Valid(r, c)).solved) {
+                continue;
             }
 
-            if board.has_contradiction() {
+            let mut attempt = board.clone();
+            for (r, c) in orbit {
+                attempt.set(Location::Valid(r, c), Cell::new(cells_per_set));
+            }
+            if attempt.count_solutions(2) != 1 {
                 continue;
             }
-            
-            board.fast_solve();
+            if let Some(target) = self.difficulty {
+                if attempt.rate().is_some_and(|rating| rating.difficulty > target) {
+                    continue;
+                }
+            }
+            board = attempt;
+        }
+        board.mark_givens();
+
+        if DEBUG {
+            println!("Final puzzle:\n{board}");
+        }
+        board
+    }
+}
+
+/// Fills a copy of the blank board with a random solution that follows its constraints.
+pub(crate) fn generate_solution(rng: &mut StdRng, blank: &Board) -> Board {
+    let cells_per_set = blank.cells_per_set();
+    if DEBUG {
+        println!("Generating {} x {} puzzle...", cells_per_set, cells_per_set);
+    }
+
+    let mut solved = false;
+    let mut board = blank.clone();
+    while !solved {
+        board = blank.clone();
+        //Conjecture: any puzzle with [size - 1] or less clues has at least one solution.
+        //For a 9x9 grid, you could get a contradiction by placing the digits 1-8 along the top row from left to right,
+        //then placing a 9 anywhere in box 3 other than R1C9
+        let mut placed_positions = Vec::with_capacity(cells_per_set);
 
-            solved = board.is_solved();
+        for digit in 0..cells_per_set-1 {
+            let mut r = rng.gen_range(0..cells_per_set);
+            let mut c = rng.gen_range(0..cells_per_set);
+            while placed_positions.contains(&(r, c)) {
+                r = rng.gen_range(0..cells_per_set);
+                c = rng.gen_range(0..cells_per_set);
+            }
+            placed_positions.push((r, c));
 
+            let given_digit = Cell::new_single_digit(cells_per_set, digit);
             if DEBUG {
-                println!("Solved: {solved}");
+                println!("Placing {given_digit} at {r}, {c}");
             }
+            board.set(Location::Valid(r, c), given_digit);
         }
 
         if DEBUG {
-            println!("Solution:\n{board}");
+            println!("{board}");
+            println!("Solving...");
         }
 
-        let mut num_given_digits = total_size;
-        while num_given_digits > 2 * cells_per_set {
-            let r = self.rng.gen_range(0..cells_per_set);
-            let c = self.rng.gen_range(0..cells_per_set);
-            let hidden_digit = Cell::new(cells_per_set);
-            board.set(Location::Valid(r, c), hidden_digit);
-            num_given_digits -= 1;
+        if board.has_contradiction() {
+            continue;
         }
+        
+        board.fast_solve();
+
+        solved = board.is_solved();
 
         if DEBUG {
-            println!("Final puzzle:\n{board}");
+            println!("Solved: {solved}");
         }
-        board
     }
-}
\ No newline at end of file
+
+    if DEBUG {
+        println!("Solution:\n{board}");
+    }
+    board
+}
@@ -0,0 +1,122 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::board::Board;
+
+/// How hard a puzzle is, based on the simplest way the solver can finish it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Difficulty {
+    /// Naked singles alone solve it.
+    Easy,
+    /// Needs hidden singles as well.
+    Medium,
+    /// Needs a few guesses.
+    Hard,
+    /// Needs more than `HARD_MAX_GUESSES` guesses.
+    Expert,
+}
+
+/// The most guesses a `Difficulty::Hard` puzzle can need.
+pub const HARD_MAX_GUESSES: usize = 5;
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert];
+
+    /// Lowercase name, as accepted by `from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(input))
+            .ok_or_else(|| format!("unknown difficulty '{input}' (expected easy, medium, hard, or expert)"))
+    }
+}
+
+/// The result of rating a puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    /// How many guesses `fast_solve` needed (0 for easy and medium puzzles).
+    pub guesses: usize,
+}
+
+impl Board {
+    /// Rates the puzzle by trying naked singles alone, then hidden singles, then guessing.
+    /// Returns `None` if the puzzle has no solution.
+    pub fn rate(&self) -> Option<Rating> {
+        if self.clone().has_contradiction() {
+            return None;
+        }
+
+        for (find_hidden_singles, difficulty) in [(false, Difficulty::Easy), (true, Difficulty::Medium)] {
+            let mut board = self.clone();
+            board.fast_reduction_loop(find_hidden_singles)?;
+            if board.is_solved() {
+                return Some(Rating { difficulty, guesses: 0 });
+            }
+        }
+
+        let stats = self.clone().fast_solve_with_stats();
+        if !stats.solved {
+            return None;
+        }
+        let difficulty = if stats.guesses <= HARD_MAX_GUESSES { Difficulty::Hard } else { Difficulty::Expert };
+        Some(Rating { difficulty, guesses: stats.guesses })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(line: &str) -> Option<Rating> {
+        Board::parse(line).unwrap_or_else(|error| panic!("{error}")).rate()
+    }
+
+    #[test]
+    fn difficulty_names_round_trip() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(difficulty.to_string().parse::<Difficulty>(), Ok(difficulty));
+        }
+        assert_eq!("Expert".parse::<Difficulty>(), Ok(Difficulty::Expert));
+        assert!("fiendish".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn rates_by_the_simplest_way_to_finish() {
+        assert_eq!(rate("1.34\n34.2\n2.43\n43.1"), Some(Rating { difficulty: Difficulty::Easy, guesses: 0 }));
+        assert_eq!(
+            rate("..43.6....5.1..4.......8..73..7..2.6......8...89.......7.6.2...6.3..1....4.....31"),
+            Some(Rating { difficulty: Difficulty::Medium, guesses: 0 })
+        );
+
+        let hard = rate("..3.9...6...7.....59483.....79...2.8........7...4.2...34.....1.....6....8.29.3...");
+        assert!(hard.is_some_and(|rating| rating.difficulty == Difficulty::Hard && (1..=HARD_MAX_GUESSES).contains(&rating.guesses)));
+        let expert = rate("2..6.5..85..48.62.............8.7.....3..6....9....46.38..1.......3..9..1.2...5..");
+        assert!(expert.is_some_and(|rating| rating.difficulty == Difficulty::Expert && rating.guesses > HARD_MAX_GUESSES));
+    }
+
+    #[test]
+    fn unsolvable_puzzles_have_no_rating() {
+        assert_eq!(rate("11..\n....\n....\n...."), None);
+        assert_eq!(rate("12..\n..3.\n....\n.4.."), None);
+    }
+}
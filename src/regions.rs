@@ -0,0 +1,284 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    board::{Board, MAX_SIZE},
+    constraint::{BoxConstraint, Constraint},
+    error::SudokuError,
+    location::Location
+};
+
+/// The labels `JigsawRegions::to_map` writes, one per region.
+const REGION_LABELS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789@#";
+
+/// The windows of Hyper Sudoku (or Windoku) have each digit once: box-sized regions one cell in from the edges,
+/// with one cell between them, so a 9x9 board has four 3x3 windows and a 16x16 board nine 4x4 ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HyperConstraint;
+
+impl HyperConstraint {
+    /// The cells of every window of a board with the given box shape, row by row.
+    pub fn windows(rows_per_box: usize, cols_per_box: usize) -> Vec<Vec<Location>> {
+        let size = rows_per_box * cols_per_box;
+        let across = size.saturating_sub(1) / (cols_per_box + 1);
+        let down = size.saturating_sub(1) / (rows_per_box + 1);
+        let mut windows = Vec::with_capacity(across * down);
+        for i in 0..down {
+            for j in 0..across {
+                let (top, left) = (1 + i * (rows_per_box + 1), 1 + j * (cols_per_box + 1));
+                windows.push((0..size).map(|k| Location::Valid(top + k / cols_per_box, left + k % cols_per_box)).collect());
+            }
+        }
+        windows
+    }
+}
+
+impl Constraint for HyperConstraint {
+    fn name(&self) -> &str {
+        "hyper"
+    }
+
+    fn houses(&self, board: &Board) -> Vec<Vec<Location>> {
+        Self::windows(board.rows_per_box(), board.cols_per_box())
+    }
+}
+
+/// Extra regions that can't repeat a digit, on top of the rows, cols, and boxes.
+/// Regions with a cell per digit have each digit once; smaller ones just have no digit twice.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtraRegions {
+    regions: Vec<Vec<Location>>,
+}
+
+impl ExtraRegions {
+    /// `Board::try_add_constraint` checks the regions fit the board.
+    pub fn new(regions: Vec<Vec<Location>>) -> Self {
+        Self { regions }
+    }
+
+    pub fn regions(&self) -> &[Vec<Location>] {
+        &self.regions
+    }
+}
+
+impl Constraint for ExtraRegions {
+    fn name(&self) -> &str {
+        "extra regions"
+    }
+
+    fn houses(&self, _board: &Board) -> Vec<Vec<Location>> {
+        self.regions.clone()
+    }
+}
+
+/// The irregular regions of a jigsaw board, which take the place of its boxes:
+/// connected groups of cells, one cell per digit, that have each digit once.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JigsawRegions {
+    size: usize,
+    regions: Vec<Vec<Location>>,
+    //The region of each cell, in row-major order.
+    region_of: Vec<usize>,
+}
+
+impl JigsawRegions {
+    /// Reads a region map: one line per row, with a char per cell naming its region, like
+    ///
+    /// ```text
+    /// AAABBBCCC
+    /// AABBBCCCC
+    /// ...
+    /// ```
+    ///
+    /// Any chars can be used, whitespace and blank lines are ignored, and regions are numbered in the order they first appear.
+    /// Returns an error if the map isn't square or has an unsupported size, or if a region doesn't have one cell per row
+    /// or isn't connected (through cells sharing a side).
+    pub fn parse_map(input: &str) -> Result<Self, SudokuError> {
+        let rows: Vec<Vec<char>> = input
+            .lines()
+            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>())
+            .filter(|row| !row.is_empty())
+            .collect();
+        let size = rows.len();
+        if size == 0 || size > MAX_SIZE {
+            return Err(SudokuError::WrongCellCount { expected: None, found: size * size });
+        }
+
+        let mut labels: Vec<char> = Vec::new();
+        let mut region_of = Vec::with_capacity(size * size);
+        for (r, row) in rows.iter().enumerate() {
+            if row.len() != size {
+                return Err(SudokuError::WrongRowLength { row: r, expected: size, found: row.len() });
+            }
+            for &label in row.iter() {
+                let region = match labels.iter().position(|&existing| existing == label) {
+                    Some(region) => region,
+                    None => {
+                        labels.push(label);
+                        labels.len() - 1
+                    }
+                };
+                region_of.push(region);
+            }
+        }
+        Self::from_region_of(size, region_of)
+    }
+
+    /// Checks the regions, given the region of each cell in row-major order, and builds the lists of their cells.
+    pub(crate) fn from_region_of(size: usize, region_of: Vec<usize>) -> Result<Self, SudokuError> {
+        let num_regions = region_of.iter().max().map_or(0, |&max| max + 1);
+        let mut regions = vec![Vec::new(); num_regions];
+        for (cell, &region) in region_of.iter().enumerate() {
+            regions[region].push(Location::Valid(cell / size, cell % size));
+        }
+
+        for (index, region) in regions.iter().enumerate() {
+            if region.len() != size {
+                return Err(SudokuError::WrongRegionSize { location: region[0], expected: size, found: region.len() });
+            }
+            //Flood fill from the first cell, through neighbors in the same region.
+            let mut reached = vec![region[0]];
+            let mut next = 0;
+            while let Some(&Location::Valid(r, c)) = reached.get(next) {
+                let neighbors = [(r.wrapping_sub(1), c), (r + 1, c), (r, c.wrapping_sub(1)), (r, c + 1)];
+                for (r, c) in neighbors {
+                    let location = Location::Valid(r, c);
+                    if r < size && c < size && region_of[r * size + c] == index && !reached.contains(&location) {
+                        reached.push(location);
+                    }
+                }
+                next += 1;
+            }
+            if reached.len() != region.len() {
+                return Err(SudokuError::DisconnectedRegion(region[0]));
+            }
+        }
+
+        Ok(Self { size, regions, region_of })
+    }
+
+    /// Number of cells per row, which is also the number of regions and of cells in each.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The cells of each region, in the order their first cells appear row by row.
+    pub fn regions(&self) -> &[Vec<Location>] {
+        &self.regions
+    }
+
+    /// Returns the index of the region the location is in, or `None` if it isn't on the board.
+    pub fn region_of(&self, location: Location) -> Option<usize> {
+        match location {
+            Location::Valid(r, c) if r < self.size && c < self.size => Some(self.region_of[r * self.size + c]),
+            _ => None,
+        }
+    }
+
+    /// Writes the region map `parse_map` reads, labeling the regions `A`, `B`, `C`, and so on.
+    pub fn to_map(&self) -> String {
+        let mut map = String::with_capacity(self.size * (self.size + 1));
+        for row in self.region_of.chunks(self.size) {
+            map.extend(row.iter().filter_map(|&region| REGION_LABELS.chars().nth(region)));
+            map.push('\n');
+        }
+        map
+    }
+}
+
+impl Constraint for JigsawRegions {
+    fn name(&self) -> &str {
+        "jigsaw"
+    }
+
+    fn houses(&self, _board: &Board) -> Vec<Vec<Location>> {
+        self.regions.clone()
+    }
+}
+
+impl Board {
+    /// Makes this a jigsaw board, with the given regions in place of its boxes (or of its old regions).
+    /// The box shape still sets how candidates are laid out when drawn.
+    /// Panics if the regions are for a different size of board; see `try_set_regions`.
+    pub fn set_regions(&mut self, regions: JigsawRegions) {
+        if let Err(error) = self.try_set_regions(regions) {
+            panic!("{error}");
+        }
+    }
+
+    /// Same as `set_regions`, but returns an error if the regions are for a different size of board.
+    pub fn try_set_regions(&mut self, regions: JigsawRegions) -> Result<(), SudokuError> {
+        if regions.size() != self.cells_per_set() {
+            return Err(SudokuError::WrongCellCount { expected: Some(self.cells_per_set() * self.cells_per_set()), found: regions.size() * regions.size() });
+        }
+        let mut constraints: Vec<_> = self.constraints().to_vec();
+        constraints.retain(|constraint| {
+            let constraint: &dyn Any = constraint.as_ref();
+            !constraint.is::<BoxConstraint>() && !constraint.is::<JigsawRegions>()
+        });
+        constraints.push(Arc::new(regions));
+        self.try_set_constraints(constraints)
+    }
+
+    /// Returns the index of the region (or box, on a board without `JigsawRegions`) the cell is in.
+    pub(crate) fn region_index(&self, r: usize, c: usize) -> usize {
+        match self.constraint::<JigsawRegions>() {
+            Some(regions) => regions.region_of(Location::Valid(r, c)).unwrap_or(0),
+            None => self.rows_per_box() * (r / self.rows_per_box()) + c / self.cols_per_box(),
+        }
+    }
+
+    /// Returns whether each cell, in row-major order, is in a region of a `HyperConstraint` or `ExtraRegions`,
+    /// which are shaded when the board is drawn.
+    pub(crate) fn shaded_cells(&self) -> Vec<bool> {
+        let geometry = self.geometry();
+        let mut shaded = vec![false; geometry.num_cells()];
+        let hyper = self.constraints_of::<HyperConstraint>().map(|_| HyperConstraint::windows(self.rows_per_box(), self.cols_per_box()));
+        let extra = self.constraints_of::<ExtraRegions>().map(|extra| extra.regions.clone());
+        for region in hyper.chain(extra).flatten() {
+            for location in region {
+                if let Some(cell) = geometry.index(location) {
+                    shaded[cell] = true;
+                }
+            }
+        }
+        shaded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hyper_windows_sit_one_cell_in() {
+        let windows = HyperConstraint::windows(3, 3);
+        assert_eq!(windows.len(), 4);
+        assert_eq!(windows[0][0], Location::Valid(1, 1));
+        assert_eq!(windows[3][8], Location::Valid(7, 7));
+        assert_eq!(HyperConstraint::windows(4, 4).len(), 9);
+    }
+
+    #[test]
+    fn region_map_round_trip() {
+        let regions = JigsawRegions::parse_map("xxxy\nxzyy\nzzwy\nzwww\n").unwrap();
+        assert_eq!(regions.size(), 4);
+        assert_eq!(regions.region_of(Location::Valid(1, 1)), Some(2));
+        assert_eq!(regions.to_map(), "AAAB\nACBB\nCCDB\nCDDD\n");
+        assert_eq!(JigsawRegions::parse_map(&regions.to_map()).unwrap(), regions);
+    }
+
+    #[test]
+    fn region_map_errors() {
+        assert_eq!(JigsawRegions::parse_map("AAAA\nAABB\nCCDD\nCCDD").unwrap_err(), SudokuError::WrongRegionSize { location: Location::Valid(0, 0), expected: 4, found: 6 });
+        assert_eq!(JigsawRegions::parse_map("ABBA\nCBBA\nCCDD\nCADD").unwrap_err(), SudokuError::DisconnectedRegion(Location::Valid(0, 0)));
+    }
+
+    #[test]
+    fn jigsaw_regions_replace_the_boxes() {
+        let mut board = Board::new(2, 2);
+        board.set_regions(JigsawRegions::parse_map("AAAB\nACBB\nCCDB\nCDDD").unwrap());
+        assert!(board.constraint::<BoxConstraint>().is_none());
+        assert!(board.sees(Location::Valid(0, 2), Location::Valid(1, 0)));
+        assert!(!board.sees(Location::Valid(0, 0), Location::Valid(1, 1)));
+    }
+}
@@ -0,0 +1,491 @@
+use std::{ffi::OsStr, io::IsTerminal};
+
+use crate::{
+    board::Board,
+    kropki::DotColor,
+    location::{cell_name, Location},
+    provenance::Provenance,
+    variant::DiagonalConstraint
+};
+
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[36m";
+const RED: &str = "\x1b[31m";
+const BOLD_RED: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+/// Fills the blank space of cells in a `HyperConstraint` window or `ExtraRegions` region.
+const SHADE: char = '░';
+/// Marks the bulb of a thermometer.
+pub(crate) const BULB: char = '●';
+/// Marks the cells of an arrow's circle.
+pub(crate) const CIRCLE: char = '○';
+/// Draws a white Kropki dot on the line between two cells.
+pub(crate) const WHITE_DOT: char = '◦';
+/// Draws a black Kropki dot on the line between two cells.
+pub(crate) const BLACK_DOT: char = '•';
+
+/// How `Board::render` draws a board. The default is what `Display` prints.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    pencil_marks: bool,
+    provenance: bool,
+    color: bool,
+}
+
+impl RenderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draws every unsolved cell as a `rows_per_box` x `cols_per_box` grid of its candidates,
+    /// with each candidate in the same place in every cell. Solved cells show their digit in the middle.
+    /// `{:#}` displays a board this way.
+    pub fn pencil_marks(mut self, pencil_marks: bool) -> Self {
+        self.pencil_marks = pencil_marks;
+        self
+    }
+
+    /// Draws brackets around solved digits to show where they came from:
+    /// `[5]` for givens, `(5)` for guesses, and `<5>` for a player's entries.
+    /// Below the board, lists the cells placed by each technique and at each guess depth.
+    pub fn provenance(mut self, provenance: bool) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// Colors the digits with ANSI escape codes: givens bold, digits filled in since cyan,
+    /// and digits that conflict with another in the same row, col, or box, or break another constraint, red.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Turns on `color` if standard output is a terminal and the `NO_COLOR` environment variable isn't set.
+    pub fn auto_color(self) -> Self {
+        self.color(wants_color(std::env::var_os("NO_COLOR").as_deref(), std::io::stdout().is_terminal()))
+    }
+}
+
+/// Whether to color output, given the value of `NO_COLOR` (if it's set) and whether output goes to a terminal.
+/// Following no-color.org, an empty `NO_COLOR` counts as not set.
+fn wants_color(no_color: Option<&OsStr>, is_terminal: bool) -> bool {
+    is_terminal && no_color.is_none_or(OsStr::is_empty)
+}
+
+impl Board {
+    /// Draws the board in a box-drawing frame, with thick lines between boxes.
+    /// On boards with a `DiagonalConstraint`, cells on the diagonals have `╲` or `╱` (or `╳` for both) in their corners,
+    /// the blank space of cells in a `HyperConstraint` window or `ExtraRegions` region is shaded with `░`,
+    /// thermometers have `●` on their bulbs and arrows along the rest of their cells, pointing away from the bulb,
+    /// arrow sums have `○` on their circles and arrows along their cells in the same way,
+    /// and Kropki dots are drawn as `◦` (white) or `•` (black) on the line between their cells.
+    /// When coloring, cells are givens if their provenance says so.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        self.render_with_original(None, options)
+    }
+
+    /// Same as `render`, but for a (partly) solved board, so when coloring
+    /// the givens are the cells solved in `original`, the puzzle this board was solved from.
+    pub fn render_solution(&self, original: &Board, options: &RenderOptions) -> String {
+        self.render_with_original(Some(original), options)
+    }
+
+    fn render_with_original(&self, original: Option<&Board>, options: &RenderOptions) -> String {
+        let size = self.cells_per_set();
+        let cols_per_box = self.cols_per_box();
+        //Every symbol is one char wide, so each candidate (or digit) has a space either side.
+        let (cell_width, cell_height) = if options.pencil_marks { (2 * cols_per_box + 1, self.rows_per_box()) } else { (3, 1) };
+
+        //Thick lines go between different boxes, or different regions on a jigsaw board.
+        let regions: Vec<usize> = (0..size * size).map(|cell| self.region_index(cell / size, cell % size)).collect();
+        let thick_right = |r: usize, c: usize| c + 1 < size && regions[r * size + c] != regions[r * size + c + 1];
+        let thick_below = |r: usize, c: usize| r + 1 < size && regions[r * size + c] != regions[(r + 1) * size + c];
+        //Lines inside a killer cage are dashed, so the solid ones outline the cages.
+        let (cages, sums) = self.cage_cells();
+        let same_cage = |a: usize, b: usize| cages[a].is_some() && cages[a] == cages[b];
+        let caged_right = |r: usize, c: usize| c + 1 < size && same_cage(r * size + c, r * size + c + 1);
+        let caged_below = |r: usize, c: usize| r + 1 < size && same_cage(r * size + c, (r + 1) * size + c);
+
+        let (dot_right, dot_below) = self.kropki_dots();
+
+        //The line below row `r` (or above the board for `None`, or below it for `Some(size - 1)`),
+        //with the sum of each cage starting in the row after it written at the start of its first cell,
+        //and any Kropki dot below a cell in the middle of the cell's stretch of the line.
+        let divider = |r: Option<usize>| {
+            let mut line = String::new();
+            for c in 0..size {
+                let fill = match r {
+                    Some(r) if r + 1 < size => match (thick_below(r, c), caged_below(r, c)) {
+                        (true, true) => '┉',
+                        (true, false) => '━',
+                        (false, true) => '┈',
+                        (false, false) => '─',
+                    },
+                    _ => ' ',
+                };
+                let next_row = r.map_or(0, |r| r + 1);
+                let sum = sums.get(next_row * size + c).copied().flatten().map(|sum| sum.to_string()).unwrap_or_default();
+                let mut segment: Vec<char> = sum.chars().chain(std::iter::repeat(fill)).take(cell_width).collect();
+                if let Some(color) = r.and_then(|r| dot_below[r * size + c]) {
+                    if segment[cell_width / 2] == fill {
+                        segment[cell_width / 2] = dot_mark(color);
+                    }
+                }
+                line.extend(segment);
+                if c < size - 1 {
+                    line.push(match r {
+                        None => if thick_right(0, c) { '╻' } else { '╷' },
+                        Some(r) if r + 1 == size => if thick_right(r, c) { '╹' } else { '╵' },
+                        Some(r) => crossing(thick_right(r, c), thick_right(r + 1, c), thick_below(r, c), thick_below(r, c + 1)),
+                    });
+                }
+            }
+            line.push('\n');
+            line
+        };
+
+        let shaded = self.shaded_cells();
+        //Thermometers are drawn over arrows where they cross.
+        let marks: Vec<Option<char>> = self.thermometer_marks().into_iter().zip(self.arrow_marks()).map(|(thermometer, arrow)| thermometer.or(arrow)).collect();
+        let mut conflicting = vec![false; size * size];
+        if options.color {
+            for location in self.conflicting_cells() {
+                if let Some(index) = self.geometry().index(location) {
+                    conflicting[index] = true;
+                }
+            }
+        }
+
+        let mut output = divider(None);
+        for r in 0..size {
+            let cells: Vec<Vec<String>> = (0..size).map(|c| self.render_cell(Location::Valid(r, c), shaded[r * size + c], marks[r * size + c], options)).collect();
+            let styles: Vec<Option<&str>> = (0..size)
+                .map(|c| self.cell_style(Location::Valid(r, c), original, conflicting[r * size + c], options))
+                .collect();
+            for line in 0..cell_height {
+                for (c, cell) in cells.iter().enumerate() {
+                    match styles[c] {
+                        Some(style) if cell[line].chars().any(|c| !c.is_whitespace() && c != SHADE) => output += &format!("{style}{}{RESET}", cell[line]),
+                        _ => output += &cell[line],
+                    }
+                    if let Some(color) = dot_right[r * size + c].filter(|_| line == cell_height / 2) {
+                        output.push(dot_mark(color));
+                    }
+                    else if c < size - 1 {
+                        output.push(match (thick_right(r, c), caged_right(r, c)) {
+                            (true, true) => '┋',
+                            (true, false) => '┃',
+                            (false, true) => '┊',
+                            (false, false) => '│',
+                        });
+                    }
+                }
+                output += "\n";
+            }
+            output += &divider(Some(r));
+        }
+        if options.provenance {
+            output += &self.provenance_legend();
+        }
+        output
+    }
+
+    /// One line for each technique, then each guess depth, that placed a digit, listing the cells it placed,
+    /// e.g. `hidden single: r1c4 r7c2` or `guess at depth 1: r5c5`.
+    fn provenance_legend(&self) -> String {
+        let mut groups: Vec<(Provenance, Vec<String>)> = Vec::new();
+        for cell in 0..self.cells_per_set() * self.cells_per_set() {
+            let location = Location::Valid(cell / self.cells_per_set(), cell % self.cells_per_set());
+            let provenance = self.get(location).provenance;
+            if !matches!(provenance, Provenance::Technique(_) | Provenance::Guess { .. }) || self.symbol_at(location).is_none() {
+                continue;
+            }
+            match groups.iter_mut().find(|(group, _)| *group == provenance) {
+                Some((_, cells)) => cells.push(cell_name(location)),
+                None => groups.push((provenance, vec![cell_name(location)])),
+            }
+        }
+        groups.sort_by_key(|(provenance, _)| match provenance {
+            Provenance::Technique(technique) => (0, *technique as usize),
+            Provenance::Guess { depth } => (1, *depth),
+            _ => (2, 0),
+        });
+        groups.into_iter().map(|(provenance, cells)| format!("{provenance}: {}\n", cells.join(" "))).collect()
+    }
+
+    /// Returns the ANSI escape code to color the cell with, if any.
+    fn cell_style(&self, location: Location, original: Option<&Board>, is_conflicting: bool, options: &RenderOptions) -> Option<&'static str> {
+        if !options.color || self.symbol_at(location).is_none() {
+            return None;
+        }
+        let is_given = match original {
+            Some(original) => original.symbol_at(location).is_some(),
+            None => self.get(location).is_given(),
+        };
+        match (is_conflicting, is_given) {
+            (true, true) => Some(BOLD_RED),
+            (true, false) => Some(RED),
+            (false, true) => Some(BOLD),
+            (false, false) => Some(CYAN),
+        }
+    }
+
+    /// Returns each line of the cell as drawn with the given options.
+    fn render_cell(&self, location: Location, shaded: bool, mark: Option<char>, options: &RenderOptions) -> Vec<String> {
+        let mut lines = self.render_cell_contents(location, options);
+        if self.constraint::<DiagonalConstraint>().is_some() {
+            mark_diagonals(DiagonalConstraint::diagonals_of(self.cells_per_set(), location), &mut lines);
+        }
+        if let Some(mark) = mark {
+            mark_cell(mark, &mut lines);
+        }
+        if shaded {
+            for line in lines.iter_mut() {
+                *line = line.replace(' ', &SHADE.to_string());
+            }
+        }
+        lines
+    }
+
+    fn render_cell_contents(&self, location: Location, options: &RenderOptions) -> Vec<String> {
+        let cell = self.get(location);
+        let (open, close) = if options.provenance { provenance_brackets(cell.provenance) } else { (' ', ' ') };
+        if !options.pencil_marks {
+            return match self.symbol_at(location) {
+                Some(symbol) => vec![format!("{open}{symbol}{close}")],
+                None => vec!["   ".to_string()],
+            };
+        }
+
+        let cols_per_box = self.cols_per_box();
+        let width = 2 * cols_per_box + 1;
+        let mut lines = vec![" ".repeat(width); self.rows_per_box()];
+        if cell.solved {
+            if let Some(symbol) = self.symbol_at(location) {
+                let middle = self.rows_per_box() / 2;
+                lines[middle] = format!("{}{open}{symbol}{close}{}", " ".repeat(width / 2 - 1), " ".repeat(width - width / 2 - 2));
+            }
+            return lines;
+        }
+
+        for (line_index, line) in lines.iter_mut().enumerate() {
+            *line = " ".to_string();
+            for digit in line_index * cols_per_box..(line_index + 1) * cols_per_box {
+                let possible = cell.possibilities.get(digit).copied().unwrap_or(false);
+                line.push(if possible { self.symbols().char_of(digit).unwrap_or(' ') } else { ' ' });
+                line.push(' ');
+            }
+        }
+        lines
+    }
+}
+
+/// Puts `╲` in the top left and bottom right corners of a cell on the main diagonal, and `╱` in the other two
+/// corners of a cell on the anti-diagonal, where the corners are blank. A corner on both gets `╳`.
+fn mark_diagonals((main, anti): (bool, bool), lines: &mut [String]) {
+    let last_line = lines.len().saturating_sub(1);
+    let mut marks: Vec<(usize, bool, char)> = Vec::new();
+    if main {
+        marks.extend([(0, false, '╲'), (last_line, true, '╲')]);
+    }
+    if anti {
+        marks.extend([(0, true, '╱'), (last_line, false, '╱')]);
+    }
+
+    for (line_index, at_end, mark) in marks {
+        let Some(line) = lines.get_mut(line_index) else { continue };
+        let mut chars: Vec<char> = line.chars().collect();
+        let column = if at_end { chars.len().saturating_sub(1) } else { 0 };
+        match chars.get(column) {
+            Some(' ') => chars[column] = mark,
+            Some(&existing) if existing != mark && matches!(existing, '╲' | '╱') => chars[column] = '╳',
+            _ => continue,
+        }
+        *line = chars.into_iter().collect();
+    }
+}
+
+/// The arrow pointing down or up and right or left, given how the row and col change
+/// from one cell of a thermometer or arrow to the next.
+pub(crate) fn direction_mark(down: std::cmp::Ordering, right: std::cmp::Ordering) -> char {
+    use std::cmp::Ordering::*;
+    match (down, right) {
+        (Less, Less) => '↖',
+        (Less, Equal) => '↑',
+        (Less, Greater) => '↗',
+        (Equal, Less) => '←',
+        (Equal, Greater) => '→',
+        (Greater, Less) => '↙',
+        (Greater, Equal) => '↓',
+        (Greater, Greater) => '↘',
+        (Equal, Equal) => BULB,
+    }
+}
+
+/// Returns true for the marks drawn along thermometers and arrows, which aren't part of a cell's digit.
+pub(crate) fn is_cell_mark(input: char) -> bool {
+    input == BULB || input == CIRCLE || ('←'..='↙').contains(&input)
+}
+
+/// Puts a thermometer's or arrow's mark in the middle of a blank cell, or else at the start of the cell's middle line if that's blank.
+fn mark_cell(mark: char, lines: &mut [String]) {
+    let middle = lines.len() / 2;
+    let Some(line) = lines.get_mut(middle) else { return };
+    let mut chars: Vec<char> = line.chars().collect();
+    let column = if chars.iter().all(|&c| c == ' ') { chars.len() / 2 } else { 0 };
+    if chars.get(column) == Some(&' ') {
+        chars[column] = mark;
+        *line = chars.into_iter().collect();
+    }
+}
+
+fn dot_mark(color: DotColor) -> char {
+    match color {
+        DotColor::White => WHITE_DOT,
+        DotColor::Black => BLACK_DOT,
+    }
+}
+
+/// The char where four lines meet, given which of the lines up, down, left, and right of it are thick.
+fn crossing(up: bool, down: bool, left: bool, right: bool) -> char {
+    match (up, down, left, right) {
+        (false, false, false, false) => '┼',
+        (false, false, false, true) => '┾',
+        (false, false, true, false) => '┽',
+        (false, false, true, true) => '┿',
+        (false, true, false, false) => '╁',
+        (false, true, false, true) => '╆',
+        (false, true, true, false) => '╅',
+        (false, true, true, true) => '╈',
+        (true, false, false, false) => '╀',
+        (true, false, false, true) => '╄',
+        (true, false, true, false) => '╃',
+        (true, false, true, true) => '╇',
+        (true, true, false, false) => '╂',
+        (true, true, false, true) => '╊',
+        (true, true, true, false) => '╉',
+        (true, true, true, true) => '╋',
+    }
+}
+
+fn provenance_brackets(provenance: Provenance) -> (char, char) {
+    match provenance {
+        Provenance::Given => ('[', ']'),
+        Provenance::Guess { .. } => ('(', ')'),
+        Provenance::Player => ('<', '>'),
+        Provenance::Unknown | Provenance::Technique(_) => (' ', ' '),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cell::Cell, provenance::Provenance};
+
+    /// A 2x2 board with a given 1 at r1c1, a 2 filled in at r1c2, and another 2 at r2c1, which conflicts with it.
+    fn colorful_board() -> Board {
+        let mut board = Board::new(2, 2);
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(4, 0).with_provenance(Provenance::Given));
+        board.set(Location::Valid(0, 1), Cell::new_single_digit(4, 1));
+        board.set(Location::Valid(1, 0), Cell::new_single_digit(4, 1));
+        board
+    }
+
+    #[test]
+    fn colors_givens_filled_cells_and_conflicts() {
+        let board = colorful_board();
+        assert_eq!(board.conflicting_cells(), vec![Location::Valid(0, 1), Location::Valid(1, 0)]);
+        let rendered = board.render(&RenderOptions::new().color(true));
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[1], format!("{BOLD} 1 {RESET}│{RED} 2 {RESET}┃   │   "));
+        assert_eq!(lines[3], format!("{RED} 2 {RESET}│   ┃   │   "));
+
+        let mut board = Board::new(2, 2);
+        board.set(Location::Valid(3, 3), Cell::new_single_digit(4, 3));
+        assert!(board.render(&RenderOptions::new().color(true)).contains(&format!("{CYAN} 4 {RESET}")));
+    }
+
+    #[test]
+    fn solutions_are_colored_against_the_original_puzzle() {
+        let mut original = Board::new(2, 2);
+        original.set(Location::Valid(0, 0), Cell::new_single_digit(4, 0));
+        let mut solution = original.clone();
+        solution.set(Location::Valid(0, 1), Cell::new_single_digit(4, 1));
+        let rendered = solution.render_solution(&original, &RenderOptions::new().color(true));
+        assert!(rendered.contains(&format!("{BOLD} 1 {RESET}│{CYAN} 2 {RESET}")));
+    }
+
+    #[test]
+    fn plain_without_color() {
+        let rendered = colorful_board().render(&RenderOptions::new());
+        assert!(!rendered.contains('\x1b'));
+        assert_eq!(rendered.lines().nth(1), Some(" 1 │ 2 ┃   │   "));
+        assert_eq!(rendered, colorful_board().to_string());
+    }
+
+    /// A board of the given shape with 1 solved in the top left cell, and its candidates narrowed down by naked singles.
+    fn with_one_digit_solved(rows_per_box: usize, cols_per_box: usize) -> Board {
+        let mut board = Board::new(rows_per_box, cols_per_box);
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(rows_per_box * cols_per_box, 0));
+        board.fast_reduction_loop(false);
+        board
+    }
+
+    #[test]
+    fn pencil_marks_on_2x2_boxes() {
+        //Each cell is a 2 x 2 grid of candidates, with a solved digit in the middle.
+        let expected = [
+            "     ╷     ╻     ╷     ",
+            "     │   2 ┃   2 │   2 ",
+            "  1  │ 3 4 ┃ 3 4 │ 3 4 ",
+            "─────┼─────╂─────┼─────",
+            "   2 │   2 ┃ 1 2 │ 1 2 ",
+            " 3 4 │ 3 4 ┃ 3 4 │ 3 4 ",
+            "━━━━━┿━━━━━╋━━━━━┿━━━━━",
+            "   2 │ 1 2 ┃ 1 2 │ 1 2 ",
+            " 3 4 │ 3 4 ┃ 3 4 │ 3 4 ",
+            "─────┼─────╂─────┼─────",
+            "   2 │ 1 2 ┃ 1 2 │ 1 2 ",
+            " 3 4 │ 3 4 ┃ 3 4 │ 3 4 ",
+            "     ╵     ╹     ╵     ",
+        ];
+        let board = with_one_digit_solved(2, 2);
+        assert_eq!(format!("{board:#}"), expected.join("\n") + "\n");
+        assert_eq!(board.render(&RenderOptions::new().pencil_marks(true)), format!("{board:#}"));
+    }
+
+    #[test]
+    fn pencil_marks_on_2x3_boxes() {
+        //Each cell is 2 lines of 3 candidates, matching the 2 x 3 boxes.
+        let rendered = format!("{:#}", with_one_digit_solved(2, 3));
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 1 + 6 * 3);
+        assert_eq!(lines[0], "       ╷       ╷       ╻       ╷       ╷       ");
+        assert_eq!(lines[1], "       │   2 3 │   2 3 ┃   2 3 │   2 3 │   2 3 ");
+        assert_eq!(lines[2], "   1   │ 4 5 6 │ 4 5 6 ┃ 4 5 6 │ 4 5 6 │ 4 5 6 ");
+        assert_eq!(lines[3], "───────┼───────┼───────╂───────┼───────┼───────");
+        assert_eq!(lines[4], "   2 3 │   2 3 │   2 3 ┃ 1 2 3 │ 1 2 3 │ 1 2 3 ");
+        assert_eq!(lines[5], " 4 5 6 │ 4 5 6 │ 4 5 6 ┃ 4 5 6 │ 4 5 6 │ 4 5 6 ");
+        assert_eq!(lines[6], "━━━━━━━┿━━━━━━━┿━━━━━━━╋━━━━━━━┿━━━━━━━┿━━━━━━━");
+        assert_eq!(lines[18], "       ╵       ╵       ╹       ╵       ╵       ");
+    }
+
+    #[test]
+    fn color_only_on_terminals_without_no_color() {
+        assert!(wants_color(None, true));
+        assert!(wants_color(Some(OsStr::new("")), true));
+        assert!(!wants_color(Some(OsStr::new("1")), true));
+        assert!(!wants_color(None, false));
+    }
+
+    #[test]
+    fn provenance_lists_techniques_and_guess_depths() {
+        let puzzle: Board = "....8..73.14...............7...3.....5....4.....6...2...64.52.....1.....3........".parse().unwrap();
+        let solution = puzzle.solutions(1).remove(0);
+        let rendered = solution.render(&RenderOptions::new().provenance(true));
+        assert!(rendered.contains("\nnaked single: "));
+        assert!(rendered.contains("\nguess at depth 1: "));
+        assert!(rendered.contains('[') && rendered.contains('('));
+        assert!(!solution.render(&RenderOptions::new()).contains("guess"));
+    }
+}
@@ -0,0 +1,440 @@
+use std::{any::Any, sync::Arc};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    arrow::{Arrow, Arrows},
+    board::Board,
+    cell::Cell,
+    constraint::{BoxConstraint, ColConstraint, Constraint, RowConstraint},
+    error::SudokuError,
+    killer::{Cage, KillerCages},
+    kropki::{Dot, DotColor, KropkiDots},
+    location::{cell_name, parse_cell_names, Location},
+    provenance::Provenance,
+    regions::{ExtraRegions, HyperConstraint, JigsawRegions},
+    symbols::SymbolSet,
+    thermo::{Thermometer, Thermometers},
+    variant::{AntiKingConstraint, AntiKnightConstraint, DiagonalConstraint}
+};
+
+/// The JSON form of a `Board`; see the crate docs for the schema.
+#[derive(Serialize, Deserialize)]
+struct BoardData {
+    rows_per_box: usize,
+    cols_per_box: usize,
+    #[serde(default)]
+    symbols: Option<String>,
+    #[serde(default)]
+    givens: Option<String>,
+    #[serde(default)]
+    cells: Option<Vec<CellData>>,
+    #[serde(default)]
+    constraints: Option<Vec<ConstraintData>>,
+    //Written for readers' convenience, and ignored when reading.
+    #[serde(default, skip_deserializing)]
+    metadata: Option<Metadata>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CellData {
+    candidates: String,
+    solved: bool,
+    #[serde(default)]
+    provenance: Provenance,
+}
+
+/// One of the board's constraints, tagged with its type. Lists of cells are written as cell names, e.g. `"r1c1 r1c2"`.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ConstraintData {
+    Rows,
+    Cols,
+    Boxes,
+    Diagonals,
+    Hyper,
+    AntiKnight,
+    AntiKing,
+    ExtraRegions { regions: Vec<String> },
+    Jigsaw { map: String },
+    Killer { cages: Vec<CageData> },
+    Thermometers { thermometers: Vec<String> },
+    Arrows { arrows: Vec<ArrowData> },
+    Kropki {
+        dots: Vec<DotData>,
+        #[serde(default)]
+        all_dots_given: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct CageData {
+    sum: usize,
+    cells: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArrowData {
+    circle: String,
+    cells: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DotData {
+    color: DotColor,
+    cells: String,
+}
+
+#[derive(Serialize, Default)]
+struct Metadata {
+    size: usize,
+    given_count: usize,
+    solved_count: usize,
+    solved: bool,
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let size = self.cells_per_set();
+        let mut givens = String::with_capacity(size * size);
+        let mut cells = Vec::with_capacity(size * size);
+        for r in 0..size {
+            for c in 0..size {
+                let location = Location::Valid(r, c);
+                let cell = self.get(location);
+                if !is_consistent(cell) {
+                    return Err(serde::ser::Error::custom(SudokuError::InconsistentCell(location)));
+                }
+                givens.push(self.symbol_at(location).filter(|_| cell.is_given()).unwrap_or('.'));
+                cells.push(CellData {
+                    candidates: cell
+                        .possibilities
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, &possible)| possible)
+                        .filter_map(|(digit, _)| self.symbols().char_of(digit))
+                        .collect(),
+                    solved: cell.solved,
+                    provenance: cell.provenance,
+                });
+            }
+        }
+
+        //A board is only written with every one of its constraints, so other types of constraint can't be written at all.
+        let constraints = self
+            .constraints()
+            .iter()
+            .map(|constraint| {
+                ConstraintData::from_constraint(constraint.as_ref())
+                    .ok_or_else(|| serde::ser::Error::custom(format!("can't write the \"{}\" constraint", constraint.name())))
+            })
+            .collect::<Result<Vec<ConstraintData>, S::Error>>()?;
+
+        let metadata = Metadata {
+            size,
+            given_count: cells.iter().filter(|cell| cell.provenance == Provenance::Given).count(),
+            solved_count: cells.iter().filter(|cell| cell.solved).count(),
+            solved: self.clone().is_solved(),
+        };
+        BoardData {
+            rows_per_box: self.rows_per_box(),
+            cols_per_box: self.cols_per_box(),
+            symbols: Some(self.symbols().symbols().iter().collect()),
+            givens: Some(givens),
+            cells: Some(cells),
+            constraints: Some(constraints),
+            metadata: Some(metadata),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BoardData::deserialize(deserializer)?.into_board().map_err(D::Error::custom)
+    }
+}
+
+impl BoardData {
+    fn into_board(self) -> Result<Board, SudokuError> {
+        let mut board = Board::try_new(self.rows_per_box, self.cols_per_box)?;
+        if let Some(symbols) = self.symbols {
+            board.set_symbols(SymbolSet::new(&symbols)?)?;
+        }
+        let symbols = board.symbols().clone();
+        let size = board.cells_per_set();
+        //Without a list of constraints, the board keeps the rows, cols, and boxes it starts with.
+        if let Some(constraints) = self.constraints {
+            let constraints = constraints.into_iter().enumerate().map(|(index, data)| data.into_constraint(index + 1)).collect::<Result<Vec<_>, _>>()?;
+            board.try_set_constraints(constraints)?;
+        }
+
+        //The cells say everything the givens do, so the givens are only read when there are no cells.
+        if let Some(cells) = self.cells {
+            if cells.len() != size * size {
+                return Err(SudokuError::WrongCellCount { expected: Some(size * size), found: cells.len() });
+            }
+            for (index, data) in cells.into_iter().enumerate() {
+                let mut cell = Cell { possibilities: vec![false; size], solved: data.solved, provenance: data.provenance };
+                for (offset, input) in data.candidates.chars().enumerate() {
+                    let digit = symbols.digit_of(input).ok_or(SudokuError::UnexpectedChar { line: index + 1, column: offset + 1, input })?;
+                    cell.possibilities[digit] = true;
+                }
+                let location = Location::Valid(index / size, index % size);
+                if !is_consistent(&cell) {
+                    return Err(SudokuError::InconsistentCell(location));
+                }
+                board.try_set(location, cell)?;
+            }
+        }
+        else if let Some(givens) = self.givens {
+            let givens: Vec<char> = givens.chars().collect();
+            if givens.len() != size * size {
+                return Err(SudokuError::WrongCellCount { expected: Some(size * size), found: givens.len() });
+            }
+            for (index, &input) in givens.iter().enumerate() {
+                if symbols.is_blank(input) {
+                    continue;
+                }
+                let digit = symbols.digit_of(input).ok_or(SudokuError::UnexpectedChar { line: 1, column: index + 1, input })?;
+                board.try_set(Location::Valid(index / size, index % size), Cell::new_single_digit(size, digit).with_provenance(Provenance::Given))?;
+            }
+        }
+        Ok(board)
+    }
+}
+
+/// A cell needs a candidate, and a solved one needs exactly one, or the solver can't tell the board is broken.
+fn is_consistent(cell: &Cell) -> bool {
+    let candidates = cell.num_possibilities();
+    candidates > 0 && (!cell.solved || candidates == 1)
+}
+
+impl ConstraintData {
+    /// Returns `None` for a type of constraint the JSON has no form for.
+    fn from_constraint(constraint: &dyn Constraint) -> Option<Self> {
+        let names = |cells: &[Location]| cells.iter().map(|&location| cell_name(location)).collect::<Vec<String>>().join(" ");
+        let constraint = constraint as &dyn Any;
+        let data = if constraint.is::<RowConstraint>() {
+            Self::Rows
+        }
+        else if constraint.is::<ColConstraint>() {
+            Self::Cols
+        }
+        else if constraint.is::<BoxConstraint>() {
+            Self::Boxes
+        }
+        else if constraint.is::<DiagonalConstraint>() {
+            Self::Diagonals
+        }
+        else if constraint.is::<HyperConstraint>() {
+            Self::Hyper
+        }
+        else if constraint.is::<AntiKnightConstraint>() {
+            Self::AntiKnight
+        }
+        else if constraint.is::<AntiKingConstraint>() {
+            Self::AntiKing
+        }
+        else if let Some(regions) = constraint.downcast_ref::<ExtraRegions>() {
+            Self::ExtraRegions { regions: regions.regions().iter().map(|region| names(region)).collect() }
+        }
+        else if let Some(regions) = constraint.downcast_ref::<JigsawRegions>() {
+            Self::Jigsaw { map: regions.to_map() }
+        }
+        else if let Some(cages) = constraint.downcast_ref::<KillerCages>() {
+            Self::Killer { cages: cages.cages().iter().map(|cage| CageData { sum: cage.sum(), cells: names(cage.cells()) }).collect() }
+        }
+        else if let Some(thermometers) = constraint.downcast_ref::<Thermometers>() {
+            Self::Thermometers { thermometers: thermometers.thermometers().iter().map(|thermometer| names(thermometer.cells())).collect() }
+        }
+        else if let Some(arrows) = constraint.downcast_ref::<Arrows>() {
+            Self::Arrows { arrows: arrows.arrows().iter().map(|arrow| ArrowData { circle: names(arrow.circle()), cells: names(arrow.cells()) }).collect() }
+        }
+        else if let Some(dots) = constraint.downcast_ref::<KropkiDots>() {
+            Self::Kropki {
+                dots: dots.dots().iter().map(|dot| DotData { color: dot.color(), cells: names(&[dot.cells().0, dot.cells().1]) }).collect(),
+                all_dots_given: dots.has_all_dots_given(),
+            }
+        }
+        else {
+            return None;
+        };
+        Some(data)
+    }
+
+    /// Builds the constraint, the `number`th in the list (counting from 1), which errors about its cells give as the line.
+    fn into_constraint(self, number: usize) -> Result<Arc<dyn Constraint>, SudokuError> {
+        let cells = |text: &str| parse_cell_names(text, number, 1);
+        Ok(match self {
+            Self::Rows => Arc::new(RowConstraint),
+            Self::Cols => Arc::new(ColConstraint),
+            Self::Boxes => Arc::new(BoxConstraint),
+            Self::Diagonals => Arc::new(DiagonalConstraint),
+            Self::Hyper => Arc::new(HyperConstraint),
+            Self::AntiKnight => Arc::new(AntiKnightConstraint),
+            Self::AntiKing => Arc::new(AntiKingConstraint),
+            Self::ExtraRegions { regions } => Arc::new(ExtraRegions::new(regions.iter().map(|region| cells(region)).collect::<Result<_, _>>()?)),
+            Self::Jigsaw { map } => Arc::new(JigsawRegions::parse_map(&map)?),
+            Self::Killer { cages } => {
+                let cages = cages.iter().map(|cage| Ok(Cage::new(cage.sum, cells(&cage.cells)?))).collect::<Result<_, SudokuError>>()?;
+                Arc::new(KillerCages::try_new(cages)?)
+            }
+            Self::Thermometers { thermometers } => {
+                let thermometers = thermometers.iter().map(|thermometer| Thermometer::try_new(cells(thermometer)?)).collect::<Result<_, _>>()?;
+                Arc::new(Thermometers::new(thermometers))
+            }
+            Self::Arrows { arrows } => {
+                let arrows = arrows.iter().map(|arrow| Arrow::try_new(cells(&arrow.circle)?, cells(&arrow.cells)?)).collect::<Result<_, _>>()?;
+                Arc::new(Arrows::new(arrows))
+            }
+            Self::Kropki { dots, all_dots_given } => {
+                let dots = dots
+                    .iter()
+                    .map(|dot| match cells(&dot.cells)?.as_slice() {
+                        &[a, b] => Dot::try_new(dot.color, a, b),
+                        _ => Err(SudokuError::UnknownDot { line: number, input: dot.cells.clone() }),
+                    })
+                    .collect::<Result<_, _>>()?;
+                Arc::new(KropkiDots::new(dots).all_dots_given(all_dots_given))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the board as JSON and reads it back, checking the copy is written the same way.
+    fn round_trip(board: &Board) -> Board {
+        let json = serde_json::to_string(board).unwrap();
+        let read: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&read).unwrap(), json);
+        read
+    }
+
+    fn cells(cells: &[(usize, usize)]) -> Vec<Location> {
+        cells.iter().map(|&(r, c)| Location::Valid(r, c)).collect()
+    }
+
+    #[test]
+    fn classic_board() {
+        let mut board = Board::new(3, 3);
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(9, 4).with_provenance(Provenance::Given));
+        let read = round_trip(&board);
+        assert_eq!(read.to_line(), board.to_line());
+        assert_eq!(read.constraints().len(), 3);
+    }
+
+    #[test]
+    fn givens_without_constraints() {
+        let read: Board = serde_json::from_str(r#"{ "rows_per_box": 2, "cols_per_box": 2, "givens": "1..2............" }"#).unwrap();
+        assert_eq!(read.to_line(), "1..2............");
+        assert!(read.constraint::<BoxConstraint>().is_some());
+    }
+
+    #[test]
+    fn variants() {
+        let mut board = Board::new(3, 3);
+        board.add_constraint(DiagonalConstraint);
+        board.add_constraint(HyperConstraint);
+        board.add_constraint(AntiKnightConstraint);
+        board.add_constraint(AntiKingConstraint);
+        let read = round_trip(&board);
+        assert!(read.constraint::<DiagonalConstraint>().is_some());
+        assert!(read.constraint::<HyperConstraint>().is_some());
+        assert!(read.constraint::<AntiKnightConstraint>().is_some());
+        assert!(read.constraint::<AntiKingConstraint>().is_some());
+    }
+
+    #[test]
+    fn extra_regions() {
+        let mut board = Board::new(3, 3);
+        board.add_constraint(ExtraRegions::new(vec![cells(&[(0, 0), (0, 4), (8, 8)]), cells(&[(4, 4), (5, 5)])]));
+        assert_eq!(round_trip(&board).constraint::<ExtraRegions>(), board.constraint::<ExtraRegions>());
+    }
+
+    #[test]
+    fn jigsaw_regions() {
+        let mut board = Board::new(2, 2);
+        board.set_regions(JigsawRegions::parse_map("AAAB\nACBB\nCCDB\nCDDD").unwrap());
+        let read = round_trip(&board);
+        assert_eq!(read.constraint::<JigsawRegions>(), board.constraint::<JigsawRegions>());
+        assert!(read.constraint::<BoxConstraint>().is_none());
+    }
+
+    #[test]
+    fn killer_cages() {
+        let mut board = Board::new(3, 3);
+        board.add_constraint(KillerCages::new(vec![Cage::new(3, cells(&[(0, 0), (0, 1)])), Cage::new(9, cells(&[(4, 4)]))]));
+        assert_eq!(round_trip(&board).constraint::<KillerCages>(), board.constraint::<KillerCages>());
+    }
+
+    #[test]
+    fn thermometers() {
+        let mut board = Board::new(3, 3);
+        board.add_constraint(Thermometers::new(vec![Thermometer::new(cells(&[(0, 0), (0, 1), (1, 1)])), Thermometer::new(cells(&[(8, 8), (7, 8)]))]));
+        assert_eq!(round_trip(&board).constraint::<Thermometers>(), board.constraint::<Thermometers>());
+    }
+
+    #[test]
+    fn arrows() {
+        let mut board = Board::new(3, 3);
+        board.add_constraint(Arrows::new(vec![Arrow::new(cells(&[(4, 4)]), cells(&[(4, 5), (4, 6)])), Arrow::new(cells(&[(0, 0), (0, 1)]), cells(&[(1, 1)]))]));
+        assert_eq!(round_trip(&board).constraint::<Arrows>(), board.constraint::<Arrows>());
+    }
+
+    #[test]
+    fn kropki_dots() {
+        let mut board = Board::new(3, 3);
+        let dots = vec![
+            Dot::new(DotColor::White, Location::Valid(0, 0), Location::Valid(0, 1)),
+            Dot::new(DotColor::Black, Location::Valid(3, 3), Location::Valid(4, 3)),
+        ];
+        board.add_constraint(KropkiDots::new(dots).all_dots_given(true));
+        assert_eq!(round_trip(&board).constraint::<KropkiDots>(), board.constraint::<KropkiDots>());
+    }
+
+    /// A blank 2x2 board with the given JSON as its first cell.
+    fn read_with_first_cell(cell: &str) -> Result<Board, serde_json::Error> {
+        let blank = r#"{ "candidates": "1234", "solved": false }"#;
+        let cells = [cell].into_iter().chain([blank; 15]).collect::<Vec<&str>>().join(", ");
+        serde_json::from_str(&format!(r#"{{ "rows_per_box": 2, "cols_per_box": 2, "cells": [{cells}] }}"#))
+    }
+
+    #[test]
+    fn solved_cells_need_exactly_one_candidate() {
+        assert!(read_with_first_cell(r#"{ "candidates": "1", "solved": true }"#).is_ok());
+        assert!(read_with_first_cell(r#"{ "candidates": "1", "solved": false }"#).is_ok());
+        let error = read_with_first_cell(r#"{ "candidates": "12", "solved": true }"#).unwrap_err();
+        assert!(error.to_string().starts_with(&SudokuError::InconsistentCell(Location::Valid(0, 0)).to_string()));
+
+        let mut board = Board::new(2, 2);
+        board.set(Location::Valid(1, 1), Cell { possibilities: vec![true, true, false, false], solved: true, provenance: Provenance::Unknown });
+        assert!(serde_json::to_string(&board).is_err());
+    }
+
+    #[test]
+    fn cells_need_a_candidate() {
+        let error = read_with_first_cell(r#"{ "candidates": "", "solved": false }"#).unwrap_err();
+        assert!(error.to_string().starts_with(&SudokuError::InconsistentCell(Location::Valid(0, 0)).to_string()));
+
+        let mut board = Board::new(2, 2);
+        board.set(Location::Valid(1, 1), Cell { possibilities: vec![false; 4], solved: false, provenance: Provenance::Unknown });
+        assert!(serde_json::to_string(&board).is_err());
+    }
+
+    #[test]
+    fn unknown_constraints_are_not_written() {
+        struct Custom;
+        impl Constraint for Custom {
+            fn name(&self) -> &str {
+                "custom"
+            }
+        }
+        let mut board = Board::new(3, 3);
+        board.add_constraint(Custom);
+        assert!(serde_json::to_string(&board).is_err());
+    }
+}
@@ -0,0 +1,397 @@
+use std::fmt::Write;
+
+use crate::{
+    arrow::Arrows,
+    board::Board,
+    kropki::{DotColor, KropkiDots},
+    location::Location,
+    regions::JigsawRegions,
+    thermo::Thermometers,
+    variant::DiagonalConstraint
+};
+
+const THIN_LINE: f64 = 1.0;
+const THICK_LINE: f64 = 3.0;
+const GIVEN_COLOR: &str = "#000000";
+const FILLED_COLOR: &str = "#2a5db0";
+const CANDIDATE_COLOR: &str = "#555555";
+const CELL_HIGHLIGHT: &str = "#fff2a8";
+const CANDIDATE_HIGHLIGHT: &str = "#a8e6a1";
+const DIAGONAL_COLOR: &str = "#9a9a9a";
+const SHADED_COLOR: &str = "#e4e4e4";
+const CAGE_COLOR: &str = "#333333";
+const THERMOMETER_COLOR: &str = "#cccccc";
+const ARROW_COLOR: &str = "#777777";
+
+/// How `Board::to_svg` draws a board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgOptions {
+    cell_size: f64,
+    pencil_marks: bool,
+    highlighted_cells: Vec<Location>,
+    highlighted_candidates: Vec<(Location, usize)>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 40.0,
+            pencil_marks: false,
+            highlighted_cells: Vec::new(),
+            highlighted_candidates: Vec::new(),
+        }
+    }
+}
+
+impl SvgOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Width and height of each cell, in pixels (40 by default).
+    pub fn cell_size(mut self, cell_size: f64) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Draws the candidates of every unsolved cell in a `rows_per_box` x `cols_per_box` grid inside it.
+    pub fn pencil_marks(mut self, pencil_marks: bool) -> Self {
+        self.pencil_marks = pencil_marks;
+        self
+    }
+
+    /// Shades the background of the cell, e.g. the cell a solve step placed a digit in.
+    pub fn highlight_cell(mut self, location: Location) -> Self {
+        self.highlighted_cells.push(location);
+        self
+    }
+
+    /// Shades the given digit index among the cell's pencil marks, e.g. a candidate a solve step removed.
+    /// Only drawn when pencil marks are.
+    pub fn highlight_candidate(mut self, location: Location, digit: usize) -> Self {
+        self.highlighted_candidates.push((location, digit));
+        self
+    }
+}
+
+impl Board {
+    /// Draws the board as a standalone SVG image, with thick lines around each box (or jigsaw region)
+    /// (and grey lines along the diagonals of boards with a `DiagonalConstraint`, dashed outlines around killer cages,
+    /// grey thermometers and arrows, grey cells in `HyperConstraint` windows and `ExtraRegions` regions,
+    /// and white or black Kropki dots).
+    /// Givens are black and bold, and other solved digits blue.
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let size = self.cells_per_set();
+        let cell = options.cell_size;
+        let margin = THICK_LINE;
+        let side = round(size as f64 * cell + 2.0 * margin);
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{side}" height="{side}" viewBox="0 0 {side} {side}" font-family="sans-serif">"#
+        );
+        let _ = writeln!(svg, r#"<rect width="{side}" height="{side}" fill="white"/>"#);
+
+        for (index, _) in self.shaded_cells().into_iter().enumerate().filter(|&(_, shaded)| shaded) {
+            let (x, y) = (round(margin + (index % size) as f64 * cell), round(margin + (index / size) as f64 * cell));
+            let _ = writeln!(svg, r#"<rect x="{x}" y="{y}" width="{cell}" height="{cell}" fill="{SHADED_COLOR}"/>"#);
+        }
+
+        for location in options.highlighted_cells.iter() {
+            if let Location::Valid(r, c) = *location {
+                if r < size && c < size {
+                    let (x, y) = (round(margin + c as f64 * cell), round(margin + r as f64 * cell));
+                    let _ = writeln!(svg, r#"<rect x="{x}" y="{y}" width="{cell}" height="{cell}" fill="{CELL_HIGHLIGHT}"/>"#);
+                }
+            }
+        }
+
+        if self.constraint::<DiagonalConstraint>().is_some() {
+            let (near, far) = (margin, round(side - margin));
+            for (x1, x2) in [(near, far), (far, near)] {
+                let _ = writeln!(svg, r#"<line x1="{x1}" y1="{near}" x2="{x2}" y2="{far}" stroke="{DIAGONAL_COLOR}" stroke-width="{THIN_LINE}"/>"#);
+            }
+        }
+
+        //Thermometers are grey tubes through the middles of their cells, with a round bulb, under the digits.
+        let thermometers = self.constraints_of::<Thermometers>().flat_map(|thermometers| thermometers.thermometers().iter());
+        for thermometer in thermometers {
+            let centers: Vec<(f64, f64)> = thermometer
+                .cells()
+                .iter()
+                .filter_map(|&location| match location {
+                    Location::Valid(r, c) if r < size && c < size => Some((round(margin + (c as f64 + 0.5) * cell), round(margin + (r as f64 + 0.5) * cell))),
+                    _ => None,
+                })
+                .collect();
+            let Some(&(bulb_x, bulb_y)) = centers.first() else { continue };
+            if centers.len() > 1 {
+                let points: Vec<String> = centers.iter().map(|(x, y)| format!("{x},{y}")).collect();
+                let _ = writeln!(
+                    svg,
+                    r#"<polyline points="{}" fill="none" stroke="{THERMOMETER_COLOR}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                    points.join(" "), round(0.3 * cell)
+                );
+            }
+            let _ = writeln!(svg, r#"<circle cx="{bulb_x}" cy="{bulb_y}" r="{}" fill="{THERMOMETER_COLOR}"/>"#, round(0.35 * cell));
+        }
+
+        //Arrows are grey lines from the edge of their circle through the middles of their cells, with a head at the tip.
+        //A circle of several cells is drawn as one rounded outline around them.
+        let center = |location: Location| match location {
+            Location::Valid(r, c) if r < size && c < size => Some((margin + (c as f64 + 0.5) * cell, margin + (r as f64 + 0.5) * cell)),
+            _ => None,
+        };
+        let radius = 0.4 * cell;
+        let arrow_width = round(0.05 * cell);
+        for arrow in self.constraints_of::<Arrows>().flat_map(|arrows| arrows.arrows().iter()) {
+            let circle: Vec<(f64, f64)> = arrow.circle().iter().filter_map(|&location| center(location)).collect();
+            let path: Vec<(f64, f64)> = arrow.cells().iter().filter_map(|&location| center(location)).collect();
+            if let [(x, y)] = circle[..] {
+                let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{ARROW_COLOR}" stroke-width="{arrow_width}"/>"#, round(x), round(y), round(radius));
+            }
+            else if circle.len() > 1 {
+                let points: Vec<String> = circle.iter().map(|(x, y)| format!("{},{}", round(*x), round(*y))).collect();
+                for (color, width) in [(ARROW_COLOR, 2.0 * radius + 2.0 * arrow_width), ("white", 2.0 * radius)] {
+                    let _ = writeln!(
+                        svg,
+                        r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                        points.join(" "), round(width)
+                    );
+                }
+            }
+
+            //Start at the edge of the circle cell nearest the arrow's first cell.
+            let (Some(&first), Some(&tip)) = (path.first(), path.last()) else { continue };
+            let distance = |(x1, y1): (f64, f64), (x2, y2): (f64, f64)| ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            let Some(&from) = circle.iter().min_by(|&&a, &&b| distance(a, first).total_cmp(&distance(b, first))) else { continue };
+            let length = distance(from, first);
+            let start = (from.0 + (first.0 - from.0) * radius / length, from.1 + (first.1 - from.1) * radius / length);
+            let points: Vec<String> = std::iter::once(start).chain(path.iter().copied()).map(|(x, y)| format!("{},{}", round(x), round(y))).collect();
+            let _ = writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{ARROW_COLOR}" stroke-width="{arrow_width}" stroke-linejoin="round"/>"#,
+                points.join(" ")
+            );
+            //The head's two sides go back from the tip at an angle to the last stretch of the line.
+            let before = if path.len() > 1 { path[path.len() - 2] } else { start };
+            let angle = (tip.1 - before.1).atan2(tip.0 - before.0);
+            for side in [-1.0, 1.0] {
+                let back = angle + std::f64::consts::PI + side * 0.5;
+                let (x, y) = (tip.0 + 0.2 * cell * back.cos(), tip.1 + 0.2 * cell * back.sin());
+                let _ = writeln!(
+                    svg,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{ARROW_COLOR}" stroke-width="{arrow_width}" stroke-linecap="round"/>"#,
+                    round(tip.0), round(tip.1), round(x), round(y)
+                );
+            }
+        }
+
+        //Killer cages are outlined by dashed lines just inside their cells, with each sum in the corner of the cage's first cell.
+        let (cages, sums) = self.cage_cells();
+        let inset = 0.08 * cell;
+        let in_cage = |cage: usize, r: isize, c: isize| {
+            r >= 0 && c >= 0 && (r as usize) < size && (c as usize) < size && cages[r as usize * size + c as usize] == Some(cage)
+        };
+        let dashes = format!("{} {}", round(0.1 * cell), round(0.06 * cell));
+        for (index, cage) in cages.iter().enumerate() {
+            let Some(cage) = *cage else { continue };
+            let (r, c) = ((index / size) as isize, (index % size) as isize);
+            let (center_x, center_y) = (margin + (c as f64 + 0.5) * cell, margin + (r as f64 + 0.5) * cell);
+            //Each side the cage ends at, by the direction (rows, cols) out of the cell, with the line's two ends along it.
+            for (out_r, out_c) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                if in_cage(cage, r + out_r, c + out_c) {
+                    continue;
+                }
+                let ends = [-1, 1].map(|sign: isize| {
+                    let (along_r, along_c) = (out_c.abs() * sign, out_r.abs() * sign);
+                    //Stop short of a side the cage also ends at, and reach into the next cell at an inside corner.
+                    let reach = if !in_cage(cage, r + along_r, c + along_c) {
+                        cell / 2.0 - inset
+                    }
+                    else if in_cage(cage, r + along_r + out_r, c + along_c + out_c) {
+                        cell / 2.0 + inset
+                    }
+                    else {
+                        cell / 2.0
+                    };
+                    let across = cell / 2.0 - inset;
+                    (
+                        round(center_x + out_c as f64 * across + along_c as f64 * reach),
+                        round(center_y + out_r as f64 * across + along_r as f64 * reach),
+                    )
+                });
+                let [(x1, y1), (x2, y2)] = ends;
+                let _ = writeln!(
+                    svg,
+                    r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{CAGE_COLOR}" stroke-width="{THIN_LINE}" stroke-dasharray="{dashes}"/>"#
+                );
+            }
+        }
+        let sum_font = round(0.22 * cell);
+        for (index, sum) in sums.iter().enumerate().filter_map(|(index, sum)| Some((index, (*sum)?))) {
+            let (x, y) = (margin + (index % size) as f64 * cell, margin + (index / size) as f64 * cell);
+            let width = round(0.6 * sum_font * sum.to_string().len() as f64 + 2.0);
+            let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{width}" height="{}" fill="white"/>"#, round(x + inset / 2.0), round(y + inset / 2.0), round(sum_font + 2.0));
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="{sum_font}" fill="{CAGE_COLOR}" dominant-baseline="central">{sum}</text>"#,
+                round(x + inset / 2.0 + 1.0), round(y + inset / 2.0 + 1.0 + sum_font / 2.0)
+            );
+        }
+
+        //Each candidate gets a spot in a mini grid, the same in every cell.
+        let candidate_width = round(cell / self.cols_per_box() as f64);
+        let candidate_height = round(cell / self.rows_per_box() as f64);
+        let candidate_font = round(0.7 * candidate_width.min(candidate_height));
+        let digit_font = round(0.6 * cell);
+        for r in 0..size {
+            for c in 0..size {
+                let location = Location::Valid(r, c);
+                let tile = self.get(location);
+                let (x, y) = (round(margin + c as f64 * cell), round(margin + r as f64 * cell));
+                if let (true, Some(symbol)) = (tile.solved, self.symbol_at(location)) {
+                    let (color, weight) = if tile.is_given() { (GIVEN_COLOR, "bold") } else { (FILLED_COLOR, "normal") };
+                    let _ = writeln!(
+                        svg,
+                        r#"<text x="{}" y="{}" font-size="{digit_font}" font-weight="{weight}" fill="{color}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                        round(x + cell / 2.0), round(y + cell / 2.0), escape(symbol)
+                    );
+                    continue;
+                }
+                if !options.pencil_marks {
+                    continue;
+                }
+
+                for (digit, _) in tile.possibilities.iter().enumerate().filter(|(_, &possible)| possible) {
+                    let Some(symbol) = self.symbols().char_of(digit) else { continue };
+                    let candidate_x = round(x + (digit % self.cols_per_box()) as f64 * candidate_width);
+                    let candidate_y = round(y + (digit / self.cols_per_box()) as f64 * candidate_height);
+                    if options.highlighted_candidates.contains(&(location, digit)) {
+                        let _ = writeln!(
+                            svg,
+                            r#"<rect x="{candidate_x}" y="{candidate_y}" width="{candidate_width}" height="{candidate_height}" fill="{CANDIDATE_HIGHLIGHT}"/>"#
+                        );
+                    }
+                    let _ = writeln!(
+                        svg,
+                        r#"<text x="{}" y="{}" font-size="{candidate_font}" fill="{CANDIDATE_COLOR}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                        round(candidate_x + candidate_width / 2.0), round(candidate_y + candidate_height / 2.0), escape(symbol)
+                    );
+                }
+            }
+        }
+
+        //Thin lines first, so the thick box lines are drawn over them.
+        let is_jigsaw = self.constraint::<JigsawRegions>().is_some();
+        for thick in [false, true] {
+            let width = if thick { THICK_LINE } else { THIN_LINE };
+            for line in 0..=size {
+                //A jigsaw board's regions are outlined below, so only the outer edges are full thick lines.
+                let is_box_edge = if is_jigsaw { line == 0 || line == size } else { line % self.rows_per_box() == 0 };
+                if is_box_edge == thick {
+                    let y = round(margin + line as f64 * cell);
+                    let _ = writeln!(svg, r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="black" stroke-width="{width}" stroke-linecap="square"/>"#, margin, round(side - margin));
+                }
+                let is_box_edge = if is_jigsaw { line == 0 || line == size } else { line % self.cols_per_box() == 0 };
+                if is_box_edge == thick {
+                    let x = round(margin + line as f64 * cell);
+                    let _ = writeln!(svg, r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="black" stroke-width="{width}" stroke-linecap="square"/>"#, margin, round(side - margin));
+                }
+            }
+        }
+        if is_jigsaw {
+            for r in 0..size {
+                for c in 0..size {
+                    let (x, y) = (round(margin + c as f64 * cell), round(margin + r as f64 * cell));
+                    let (right, bottom) = (round(x + cell), round(y + cell));
+                    if c + 1 < size && self.region_index(r, c) != self.region_index(r, c + 1) {
+                        let _ = writeln!(svg, r#"<line x1="{right}" y1="{y}" x2="{right}" y2="{bottom}" stroke="black" stroke-width="{THICK_LINE}" stroke-linecap="square"/>"#);
+                    }
+                    if r + 1 < size && self.region_index(r, c) != self.region_index(r + 1, c) {
+                        let _ = writeln!(svg, r#"<line x1="{x}" y1="{bottom}" x2="{right}" y2="{bottom}" stroke="black" stroke-width="{THICK_LINE}" stroke-linecap="square"/>"#);
+                    }
+                }
+            }
+        }
+
+        //Kropki dots sit on top of the lines, in the middle of the side their cells share.
+        let dot_radius = round(0.1 * cell);
+        for dot in self.constraints_of::<KropkiDots>().flat_map(|dots| dots.dots().iter()) {
+            let (a, b) = dot.cells();
+            let (Some((x1, y1)), Some((x2, y2))) = (center(a), center(b)) else { continue };
+            let fill = match dot.color() {
+                DotColor::White => "white",
+                DotColor::Black => "black",
+            };
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{dot_radius}" fill="{fill}" stroke="black" stroke-width="{THIN_LINE}"/>"#,
+                round((x1 + x2) / 2.0), round((y1 + y2) / 2.0)
+            );
+        }
+
+        svg += "</svg>\n";
+        svg
+    }
+}
+
+/// Rounds to two decimal places, which is plenty for pixels and keeps the SVG short.
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Escapes a symbol for use as SVG text.
+fn escape(symbol: char) -> String {
+    match symbol {
+        '&' => "&amp;".to_string(),
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        '"' => "&quot;".to_string(),
+        _ => symbol.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cell::Cell, provenance::Provenance};
+
+    #[test]
+    fn draws_digits_by_how_they_were_placed() {
+        let mut board = Board::new(2, 2);
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(4, 0).with_provenance(Provenance::Given));
+        board.set(Location::Valid(3, 3), Cell::new_single_digit(4, 1));
+        let svg = board.to_svg(&SvgOptions::new());
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="166" height="166""#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches(&format!(r#"font-weight="bold" fill="{GIVEN_COLOR}""#)).count(), 1);
+        assert_eq!(svg.matches(&format!(r#"font-weight="normal" fill="{FILLED_COLOR}""#)).count(), 1);
+        assert!(!svg.contains(CANDIDATE_COLOR));
+    }
+
+    #[test]
+    fn pencil_marks_and_highlights() {
+        let mut board = Board::new(2, 2);
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(4, 0));
+        let options = SvgOptions::new().cell_size(60.0).pencil_marks(true).highlight_cell(Location::Valid(1, 1)).highlight_candidate(Location::Valid(0, 1), 2);
+        let svg = board.to_svg(&options);
+        assert!(svg.contains(r#"width="246""#));
+        assert_eq!(svg.matches(&format!(r#"fill="{CANDIDATE_COLOR}""#)).count(), 15 * 4);
+        assert_eq!(svg.matches(CELL_HIGHLIGHT).count(), 1);
+        assert_eq!(svg.matches(CANDIDATE_HIGHLIGHT).count(), 1);
+    }
+
+    #[test]
+    fn draws_constraints() {
+        let mut board = Board::new(3, 3);
+        board.add_constraint(DiagonalConstraint);
+        board.add_constraint(Thermometers::parse("r1c1 r1c2 r1c3").unwrap());
+        board.add_constraint(KropkiDots::parse("white r5c5 r5c6\nblack r5c5 r6c5").unwrap());
+        let svg = board.to_svg(&SvgOptions::new());
+        assert_eq!(svg.matches(DIAGONAL_COLOR).count(), 2);
+        assert_eq!(svg.matches(THERMOMETER_COLOR).count(), 2);
+        assert_eq!(svg.matches(r#"fill="black" stroke="black""#).count(), 1);
+        assert_eq!(svg.matches(r#"fill="white" stroke="black""#).count(), 1);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    board::MAX_SIZE,
+    cell::DIGIT_CHARS,
+    error::SudokuError
+};
+
+/// The chars used to write each digit of a board, e.g. `1-9` for a classic board,
+/// `0-9A-F` for a hex board, or the letters of a word for Wordoku.
+/// Digit index `i` is written as the `i`th symbol.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SymbolSet {
+    symbols: Vec<char>,
+    //True if no two symbols differ only by case, so input can be read case-insensitively.
+    case_insensitive: bool,
+}
+
+impl SymbolSet {
+    /// Creates a symbol set from the given chars, in digit order.
+    /// Returns an error if there are too many, if any repeat, or if any are whitespace, `.`, or grid-drawing chars.
+    pub fn new(symbols: &str) -> Result<Self, SudokuError> {
+        let symbols: Vec<char> = symbols.chars().collect();
+        if symbols.is_empty() || symbols.len() > MAX_SIZE {
+            return Err(SudokuError::WrongSymbolCount { expected: None, found: symbols.len() });
+        }
+        for (index, &symbol) in symbols.iter().enumerate() {
+            if is_reserved(symbol) {
+                return Err(SudokuError::InvalidSymbol(symbol));
+            }
+            if symbols[..index].contains(&symbol) {
+                return Err(SudokuError::DuplicateSymbol(symbol));
+            }
+        }
+
+        let mut lowercase: Vec<char> = symbols.iter().map(char::to_ascii_lowercase).collect();
+        lowercase.sort_unstable();
+        lowercase.dedup();
+        let case_insensitive = lowercase.len() == symbols.len();
+        Ok(Self { symbols, case_insensitive })
+    }
+
+    /// The default symbols for a board with `size` cells per set: the first `size` chars of `DIGIT_CHARS`,
+    /// so `1-9` for 9x9 and `1-9A-G` for 16x16.
+    pub fn digits(size: usize) -> Result<Self, SudokuError> {
+        if size == 0 || size > MAX_SIZE {
+            return Err(SudokuError::WrongSymbolCount { expected: None, found: size });
+        }
+        Self::new(&DIGIT_CHARS[..size])
+    }
+
+    /// `0-9A-F`, the usual symbols for 16x16 boards.
+    pub fn hex() -> Self {
+        Self::from_valid("0123456789ABCDEF")
+    }
+
+    /// The first `size` capital letters, e.g. `A-Y` for 25x25 boards.
+    pub fn letters(size: usize) -> Result<Self, SudokuError> {
+        const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        if size == 0 || size > LETTERS.len() {
+            return Err(SudokuError::WrongSymbolCount { expected: None, found: size });
+        }
+        Self::new(&LETTERS[..size])
+    }
+
+    fn from_valid(symbols: &str) -> Self {
+        match Self::new(symbols) {
+            Ok(symbol_set) => symbol_set,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Number of symbols, which is the number of cells per set of the boards it can write.
+    pub fn size(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// The symbols in digit order.
+    pub fn symbols(&self) -> &[char] {
+        &self.symbols
+    }
+
+    /// Returns the symbol for the given digit index, or `None` if it's too large.
+    pub fn char_of(&self, digit: usize) -> Option<char> {
+        self.symbols.get(digit).copied()
+    }
+
+    /// Returns the digit index of the given symbol, or `None` if it isn't one.
+    /// Letters are matched case-insensitively unless the set has symbols that differ only by case.
+    pub fn digit_of(&self, input: char) -> Option<usize> {
+        self.symbols.iter().position(|&symbol| symbol == input).or_else(|| {
+            if self.case_insensitive {
+                self.symbols.iter().position(|symbol| symbol.eq_ignore_ascii_case(&input))
+            }
+            else {
+                None
+            }
+        })
+    }
+
+    /// Returns true if the char means a blank cell: `.` always does, and `0` does unless it's one of the symbols.
+    pub fn is_blank(&self, input: char) -> bool {
+        input == '.' || (input == '0' && !self.symbols.contains(&'0'))
+    }
+}
+
+/// Chars that can't be symbols because they mean something else in the formats boards are read from and written to.
+fn is_reserved(input: char) -> bool {
+    input.is_whitespace() || input.is_control() || matches!(input, '.' | '|' | '+' | '-' | '=' | ':' | '*' | '░') || ('\u{2500}'..='\u{257F}').contains(&input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_sets() {
+        let digits = SymbolSet::digits(16).unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!(digits.char_of(0), Some('1'));
+        assert_eq!(digits.char_of(15), Some('G'));
+        assert_eq!(digits.char_of(16), None);
+        assert_eq!(SymbolSet::hex().digit_of('0'), Some(0));
+        assert_eq!(SymbolSet::letters(25).map(|letters| letters.char_of(24)), Ok(Some('Y')));
+        assert_eq!(SymbolSet::digits(0).err(), Some(SudokuError::WrongSymbolCount { expected: None, found: 0 }));
+        assert_eq!(SymbolSet::letters(27).err(), Some(SudokuError::WrongSymbolCount { expected: None, found: 27 }));
+    }
+
+    #[test]
+    fn case_insensitive_unless_symbols_differ_by_case() {
+        let word = SymbolSet::new("WORDSMITH").unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!(word.digit_of('w'), Some(0));
+        assert_eq!(word.digit_of('h'), Some(8));
+        assert_eq!(word.digit_of('x'), None);
+
+        let mixed = SymbolSet::new("aA").unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!(mixed.digit_of('A'), Some(1));
+        assert_eq!(mixed.digit_of('b'), None);
+        assert_eq!(SymbolSet::new("ab").map(|set| set.digit_of('B')), Ok(Some(1)));
+    }
+
+    #[test]
+    fn blanks() {
+        assert!(SymbolSet::digits(9).is_ok_and(|digits| digits.is_blank('0') && digits.is_blank('.') && !digits.is_blank('1')));
+        assert!(!SymbolSet::hex().is_blank('0'));
+        assert!(SymbolSet::hex().is_blank('.'));
+    }
+
+    #[test]
+    fn rejects_bad_symbols() {
+        assert_eq!(SymbolSet::new("12.4").err(), Some(SudokuError::InvalidSymbol('.')));
+        assert_eq!(SymbolSet::new("1 34").err(), Some(SudokuError::InvalidSymbol(' ')));
+        assert_eq!(SymbolSet::new("1*34").err(), Some(SudokuError::InvalidSymbol('*')));
+        assert_eq!(SymbolSet::new("1┼34").err(), Some(SudokuError::InvalidSymbol('┼')));
+        assert_eq!(SymbolSet::new("1231").err(), Some(SudokuError::DuplicateSymbol('1')));
+        assert_eq!(SymbolSet::new("").err(), Some(SudokuError::WrongSymbolCount { expected: None, found: 0 }));
+    }
+}
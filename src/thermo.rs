@@ -0,0 +1,254 @@
+use crate::{
+    board::Board,
+    constraint::Constraint,
+    error::SudokuError,
+    location::{cell_name, parse_cell_names, Location},
+    render::{direction_mark, BULB}
+};
+
+/// A path of cells, each touching the one before it (diagonally counts), whose digits strictly increase from the bulb.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Thermometer {
+    cells: Vec<Location>,
+}
+
+impl Thermometer {
+    /// Panics if a cell doesn't touch the one before it; see `try_new`.
+    pub fn new(cells: Vec<Location>) -> Self {
+        match Self::try_new(cells) {
+            Ok(thermometer) => thermometer,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /// Takes the cells from the bulb to the tip, and returns an error if a cell doesn't touch the one before it.
+    /// `Board::try_add_constraint` checks the cells are on the board.
+    pub fn try_new(cells: Vec<Location>) -> Result<Self, SudokuError> {
+        for pair in cells.windows(2) {
+            let (Location::Valid(r1, c1), Location::Valid(r2, c2)) = (pair[0], pair[1]) else {
+                return Err(SudokuError::InvalidLocation(if matches!(pair[0], Location::Valid(..)) { pair[1] } else { pair[0] }));
+            };
+            if r1.abs_diff(r2).max(c1.abs_diff(c2)) != 1 {
+                return Err(SudokuError::BrokenThermometer(pair[1]));
+            }
+        }
+        Ok(Self { cells })
+    }
+
+    /// The cells from the bulb to the tip.
+    pub fn cells(&self) -> &[Location] {
+        &self.cells
+    }
+
+    /// The cell with the smallest digit.
+    pub fn bulb(&self) -> Option<Location> {
+        self.cells.first().copied()
+    }
+}
+
+/// The thermometers of a thermo puzzle. Thermometers can share cells, e.g. to branch off one another.
+/// Thermo puzzles usually have few givens, since the thermometers narrow the digits down so much.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Thermometers {
+    thermometers: Vec<Thermometer>,
+}
+
+impl Thermometers {
+    pub fn new(thermometers: Vec<Thermometer>) -> Self {
+        Self { thermometers }
+    }
+
+    /// Reads thermometers written a line each, as their cells from the bulb to the tip, like
+    ///
+    /// ```text
+    /// r1c1 r1c2 r2c3
+    /// r9c9, r8c9, r7c9
+    /// ```
+    ///
+    /// Rows and cols count from 1, and cells are separated by whitespace or commas.
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(input: &str) -> Result<Self, SudokuError> {
+        let mut thermometers = Vec::new();
+        for (line, text) in input.lines().enumerate() {
+            if text.trim().is_empty() || text.trim_start().starts_with('#') {
+                continue;
+            }
+            let cells = parse_cell_names(text, line + 1, 1)?;
+            thermometers.push(Thermometer::try_new(cells)?);
+        }
+        Ok(Self::new(thermometers))
+    }
+
+    /// Writes the thermometers in the form `parse` reads.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for thermometer in self.thermometers.iter() {
+            let cells: Vec<String> = thermometer.cells.iter().map(|&location| cell_name(location)).collect();
+            text += &cells.join(" ");
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn thermometers(&self) -> &[Thermometer] {
+        &self.thermometers
+    }
+}
+
+impl Constraint for Thermometers {
+    fn name(&self) -> &str {
+        "thermometers"
+    }
+
+    fn houses(&self, _board: &Board) -> Vec<Vec<Location>> {
+        self.thermometers.iter().map(|thermometer| thermometer.cells.clone()).collect()
+    }
+
+    fn propagate(&self, board: &mut Board) -> Option<bool> {
+        let size = board.cells_per_set();
+        let mut found_something = false;
+        for thermometer in self.thermometers.iter() {
+            //Each cell's digit is above the smallest the cell before it can have...
+            let mut lowest = 0;
+            for &location in thermometer.cells.iter() {
+                let cell = board.get_mut(location);
+                for possible in cell.possibilities.iter_mut().take(lowest).filter(|possible| **possible) {
+                    *possible = false;
+                    found_something = true;
+                }
+                lowest = cell.possibilities.iter().position(|&possible| possible)? + 1;
+            }
+            //...and below the largest the cell after it can have.
+            let mut highest = size;
+            for &location in thermometer.cells.iter().rev() {
+                let cell = board.get_mut(location);
+                for possible in cell.possibilities.iter_mut().skip(highest).filter(|possible| **possible) {
+                    *possible = false;
+                    found_something = true;
+                }
+                highest = cell.possibilities.iter().rposition(|&possible| possible)?;
+            }
+        }
+        Some(found_something)
+    }
+
+    fn has_contradiction(&self, board: &Board) -> bool {
+        let size = board.cells_per_set();
+        self.thermometers.iter().any(|thermometer| {
+            let last = thermometer.cells.len().saturating_sub(1);
+            let mut previous: Option<(usize, usize)> = None;
+            for (index, &location) in thermometer.cells.iter().enumerate() {
+                let Some(digit) = board.get(location).get_single_index() else { continue };
+                //There have to be enough digits below it for the cells before it, and above it for the cells after it.
+                if digit < index || digit + (last - index) >= size {
+                    return true;
+                }
+                if previous.is_some_and(|(previous_index, previous_digit)| digit < previous_digit + (index - previous_index)) {
+                    return true;
+                }
+                previous = Some((index, digit));
+            }
+            false
+        })
+    }
+
+    fn conflicting_cells(&self, board: &Board) -> Vec<Location> {
+        let size = board.cells_per_set();
+        let mut conflicting = Vec::new();
+        for thermometer in self.thermometers.iter() {
+            let last = thermometer.cells.len().saturating_sub(1);
+            let digits: Vec<Option<usize>> = thermometer.cells.iter().map(|&location| board.get(location).get_single_index()).collect();
+            for (index, &location) in thermometer.cells.iter().enumerate() {
+                let Some(digit) = digits[index] else { continue };
+                let out_of_range = digit < index || digit + (last - index) >= size;
+                //Any solved cell it's too close to, below or above it on the thermometer.
+                let out_of_order = digits.iter().enumerate().any(|(other_index, other)| match *other {
+                    Some(other) if other_index < index => digit < other + (index - other_index),
+                    Some(other) if other_index > index => other < digit + (other_index - index),
+                    _ => false,
+                });
+                if out_of_range || out_of_order {
+                    conflicting.push(location);
+                }
+            }
+        }
+        conflicting
+    }
+}
+
+impl Board {
+    /// For each cell in row-major order, the mark the terminal drawing puts in it for thermometers:
+    /// `●` on a bulb, and otherwise an arrow pointing away from the cell before it on its thermometer.
+    pub(crate) fn thermometer_marks(&self) -> Vec<Option<char>> {
+        let geometry = self.geometry();
+        let mut marks = vec![None; geometry.num_cells()];
+        let thermometers = self.constraints_of::<Thermometers>().flat_map(|thermometers| thermometers.thermometers.iter());
+        for thermometer in thermometers {
+            for (index, &location) in thermometer.cells.iter().enumerate() {
+                let Some(cell) = geometry.index(location) else { continue };
+                let mark = match (index.checked_sub(1).map(|previous| thermometer.cells[previous]), location) {
+                    (Some(Location::Valid(r1, c1)), Location::Valid(r2, c2)) => direction_mark(r2.cmp(&r1), c2.cmp(&c1)),
+                    _ => BULB,
+                };
+                //Bulbs are drawn over arrows, and otherwise the first thermometer through a cell is drawn.
+                if marks[cell].is_none() || mark == BULB {
+                    marks[cell] = Some(mark);
+                }
+            }
+        }
+        marks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+
+    /// The cell's candidates, counting from 1.
+    fn candidates(board: &Board, r: usize, c: usize) -> Vec<usize> {
+        board.get(Location::Valid(r, c)).possibilities.iter().enumerate().filter(|(_, &possible)| possible).map(|(digit, _)| digit + 1).collect()
+    }
+
+    #[test]
+    fn parse_and_to_text_round_trip() {
+        let thermometers = Thermometers::parse("# bulbs first\nr1c1 r1c2 r2c3\n\nr9c9, r8c9, r7c9\n").unwrap();
+        assert_eq!(thermometers.thermometers().len(), 2);
+        assert_eq!(thermometers.thermometers()[1].bulb(), Some(Location::Valid(8, 8)));
+        assert_eq!(thermometers.to_text(), "r1c1 r1c2 r2c3\nr9c9 r8c9 r7c9\n");
+        assert_eq!(Thermometers::parse(&thermometers.to_text()).unwrap(), thermometers);
+    }
+
+    #[test]
+    fn parse_rejects_cells_that_dont_touch() {
+        assert_eq!(Thermometers::parse("r1c1 r1c3").unwrap_err(), SudokuError::BrokenThermometer(Location::Valid(0, 2)));
+    }
+
+    #[test]
+    fn propagation_leaves_room_along_the_thermometer() {
+        let mut board = Board::new(3, 3);
+        let thermometers = Thermometers::parse("r1c1 r1c2 r1c3 r1c4").unwrap();
+        board.add_constraint(thermometers.clone());
+        assert_eq!(thermometers.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 0, 0), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(candidates(&board, 0, 3), vec![4, 5, 6, 7, 8, 9]);
+
+        board.set(Location::Valid(0, 1), Cell::new_single_digit(9, 4));
+        assert_eq!(thermometers.propagate(&mut board), Some(true));
+        assert_eq!(candidates(&board, 0, 0), vec![1, 2, 3, 4]);
+        assert_eq!(candidates(&board, 0, 2), vec![6, 7, 8]);
+        assert_eq!(candidates(&board, 0, 3), vec![7, 8, 9]);
+        assert_eq!(thermometers.propagate(&mut board), Some(false));
+    }
+
+    #[test]
+    fn out_of_order_digits_conflict() {
+        let mut board = Board::new(3, 3);
+        let thermometers = Thermometers::parse("r1c1 r1c2 r1c3").unwrap();
+        board.add_constraint(thermometers.clone());
+        board.set(Location::Valid(0, 0), Cell::new_single_digit(9, 5));
+        board.set(Location::Valid(0, 2), Cell::new_single_digit(9, 6));
+        assert!(thermometers.has_contradiction(&board));
+        assert_eq!(thermometers.conflicting_cells(&board), vec![Location::Valid(0, 0), Location::Valid(0, 2)]);
+    }
+}
@@ -0,0 +1,172 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use crate::{board::Board, constraint::Constraint, location::Location, regions::HyperConstraint};
+
+/// Both main diagonals have each digit once, as in Sudoku-X.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiagonalConstraint;
+
+impl DiagonalConstraint {
+    /// Returns whether the location is on the main diagonal (top left to bottom right)
+    /// and whether it's on the anti-diagonal (top right to bottom left) of a board with the given number of cells per set.
+    pub fn diagonals_of(cells_per_set: usize, location: Location) -> (bool, bool) {
+        match location {
+            Location::Valid(r, c) if r < cells_per_set && c < cells_per_set => (r == c, r + c == cells_per_set - 1),
+            _ => (false, false),
+        }
+    }
+}
+
+impl Constraint for DiagonalConstraint {
+    fn name(&self) -> &str {
+        "diagonal"
+    }
+
+    fn houses(&self, board: &Board) -> Vec<Vec<Location>> {
+        let size = board.cells_per_set();
+        vec![
+            (0..size).map(|i| Location::Valid(i, i)).collect(),
+            (0..size).map(|i| Location::Valid(i, size - 1 - i)).collect(),
+        ]
+    }
+}
+
+/// No two cells a knight's move apart (two cells one way and one the other) have the same digit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AntiKnightConstraint;
+
+impl Constraint for AntiKnightConstraint {
+    fn name(&self) -> &str {
+        "anti-knight"
+    }
+
+    fn houses(&self, board: &Board) -> Vec<Vec<Location>> {
+        pairs_apart(board.cells_per_set(), &[(1, 2), (1, -2), (2, 1), (2, -1)])
+    }
+}
+
+/// No two cells a king's move apart have the same digit.
+/// Only diagonal neighbors need a house of their own, since cells next to each other in a row or col already share one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AntiKingConstraint;
+
+impl Constraint for AntiKingConstraint {
+    fn name(&self) -> &str {
+        "anti-king"
+    }
+
+    fn houses(&self, board: &Board) -> Vec<Vec<Location>> {
+        pairs_apart(board.cells_per_set(), &[(1, 1), (1, -1)])
+    }
+}
+
+/// Every pair of cells on a board of the given size that are one of the given (row, col) steps apart, as two-cell houses.
+/// Steps go down, so each pair is listed once.
+fn pairs_apart(size: usize, steps: &[(usize, isize)]) -> Vec<Vec<Location>> {
+    let mut pairs = Vec::new();
+    for r in 0..size {
+        for c in 0..size {
+            for &(down, across) in steps {
+                let Some(other) = c.checked_add_signed(across) else {
+                    continue;
+                };
+                if r + down < size && other < size {
+                    pairs.push(vec![Location::Valid(r, c), Location::Valid(r + down, other)]);
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// Variants whose rules need nothing but the board, so they can be picked by name, e.g. on the command line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// Sudoku-X; see `DiagonalConstraint`.
+    Diagonal,
+    /// Hyper Sudoku, also called Windoku; see `HyperConstraint`.
+    Hyper,
+    /// See `AntiKnightConstraint`.
+    AntiKnight,
+    /// See `AntiKingConstraint`.
+    AntiKing,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 4] = [Variant::Diagonal, Variant::Hyper, Variant::AntiKnight, Variant::AntiKing];
+
+    /// Lowercase name, as accepted by `from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Diagonal => "diagonal",
+            Variant::Hyper => "hyper",
+            Variant::AntiKnight => "anti-knight",
+            Variant::AntiKing => "anti-king",
+        }
+    }
+
+    /// The constraint to add to a board (or `PuzzleGenerator`) for this variant.
+    pub fn constraint(self) -> Arc<dyn Constraint> {
+        match self {
+            Variant::Diagonal => Arc::new(DiagonalConstraint),
+            Variant::Hyper => Arc::new(HyperConstraint),
+            Variant::AntiKnight => Arc::new(AntiKnightConstraint),
+            Variant::AntiKing => Arc::new(AntiKingConstraint),
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|variant| variant.name().eq_ignore_ascii_case(input))
+            .ok_or_else(|| format!("unknown variant '{input}' (expected diagonal, hyper, anti-knight, or anti-king)"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_names_round_trip() {
+        for variant in Variant::ALL {
+            assert_eq!(variant.name().parse::<Variant>(), Ok(variant));
+        }
+        assert_eq!("Anti-Knight".parse::<Variant>(), Ok(Variant::AntiKnight));
+        assert!("knight".parse::<Variant>().is_err());
+    }
+
+    #[test]
+    fn diagonals_of_a_cell() {
+        assert_eq!(DiagonalConstraint::diagonals_of(9, Location::Valid(4, 4)), (true, true));
+        assert_eq!(DiagonalConstraint::diagonals_of(9, Location::Valid(2, 6)), (false, true));
+        assert_eq!(DiagonalConstraint::diagonals_of(9, Location::Valid(9, 9)), (false, false));
+    }
+
+    #[test]
+    fn variants_add_and_remove_peers() {
+        let mut board = Board::new(3, 3);
+        let center = Location::Valid(4, 4);
+        let (knight, far) = (Location::Valid(6, 5), Location::Valid(0, 0));
+        board.add_constraint(AntiKnightConstraint);
+        assert!(board.sees(center, knight) && !board.sees(Location::Valid(2, 2), Location::Valid(3, 3)));
+        board.add_constraint(AntiKingConstraint);
+        assert!(board.sees(Location::Valid(2, 2), Location::Valid(3, 3)));
+        assert!(!board.sees(center, far));
+        board.add_constraint(DiagonalConstraint);
+        assert!(board.sees(center, far));
+
+        assert_eq!(board.remove_constraints_of::<AntiKnightConstraint>(), 1);
+        assert!(!board.sees(center, knight) && board.sees(Location::Valid(2, 2), Location::Valid(3, 3)));
+    }
+}